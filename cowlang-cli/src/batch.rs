@@ -0,0 +1,207 @@
+use anyhow::{Context, Result};
+use cowlang::batch::{Case, Job, Outcome, Verdict};
+use serde_json::json;
+use std::{num::NonZeroUsize, path::Path, process::ExitCode};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The program file paths
+    #[arg(required = true)]
+    programs: Vec<Box<Path>>,
+
+    /// A directory of `<name>.in` and `<name>.out` test cases
+    #[arg(short, long)]
+    cases: Box<Path>,
+
+    #[command(flatten)]
+    source: crate::source::Options,
+
+    #[command(flatten)]
+    limits: Limits,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Limits {
+    /// The maximum number of steps per run
+    #[arg(long)]
+    max_steps: Option<u64>,
+
+    /// The maximum number of memory cells per run
+    #[arg(long)]
+    max_memory: Option<usize>,
+
+    /// The number of programs to run at once
+    #[arg(short = 'j', long)]
+    threads: Option<NonZeroUsize>,
+
+    /// The dialect to run programs in
+    #[arg(long, value_enum, default_value = "reference")]
+    dialect: Dialect,
+}
+
+#[derive(Debug, Copy, Clone, clap::ValueEnum)]
+enum Dialect {
+    /// The original COW implementation, which the other tools follow
+    Reference,
+    /// Loops that work like Brainfuck's brackets
+    Structured,
+}
+
+impl Dialect {
+    fn dialect(self) -> cowlang::Dialect {
+        match self {
+            Self::Reference => cowlang::Dialect::REFERENCE,
+            Self::Structured => cowlang::Dialect::STRUCTURED,
+        }
+    }
+}
+
+impl Limits {
+    pub fn options(&self) -> cowlang::batch::Options {
+        let defaults = cowlang::batch::Options::default();
+
+        cowlang::batch::Options {
+            max_steps: self.max_steps.unwrap_or(defaults.max_steps),
+            max_memory: self.max_memory.unwrap_or(defaults.max_memory),
+            threads: self.threads.unwrap_or(defaults.threads),
+            dialect: self.dialect.dialect(),
+        }
+    }
+}
+
+pub async fn run(args: Args) -> Result<ExitCode> {
+    let cases = read_cases(&args.cases).await?;
+    let mut jobs = Vec::with_capacity(args.programs.len() * cases.len());
+
+    for path in &args.programs {
+        let program = crate::source::program(path, &args.source).await?;
+
+        for case in &cases {
+            jobs.push(Job {
+                name: path.display().to_string(),
+                program: program.clone(),
+                case: case.clone(),
+            });
+        }
+    }
+
+    let outcomes = cowlang::batch::run(&jobs, &args.limits.options());
+    report(&outcomes, args.json)
+}
+
+pub fn report(outcomes: &[Outcome], json: bool) -> Result<ExitCode> {
+    if json {
+        print_json(outcomes)?;
+    } else {
+        print_text(outcomes);
+    }
+
+    if outcomes.iter().all(Outcome::passed) {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+async fn read_cases(dir: &Path) -> Result<Vec<Case>> {
+    let mut cases = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir)
+        .await
+        .with_context(|| format!("failed to read {}", dir.display()))?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+
+        if path.extension().is_none_or(|ext| ext != "out") {
+            continue;
+        }
+
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        let expected = tokio::fs::read_to_string(&path).await?;
+        let input = match tokio::fs::read_to_string(path.with_extension("in")).await {
+            Ok(input) => input,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error.into()),
+        };
+
+        cases.push(Case {
+            name: name.to_string(),
+            input,
+            expected,
+            max_steps: None,
+        });
+    }
+
+    cases.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(cases)
+}
+
+fn print_text(outcomes: &[Outcome]) {
+    for outcome in outcomes {
+        let label = match outcome.verdict {
+            Verdict::Pass => "PASS",
+            _ => "FAIL",
+        };
+
+        println!(
+            "{label} {} [{}] {} steps in {:.2?}",
+            outcome.name, outcome.case, outcome.steps, outcome.duration,
+        );
+
+        match &outcome.verdict {
+            Verdict::Pass => {}
+            Verdict::Fail { diff } => {
+                for line in diff.lines() {
+                    println!("    {line}");
+                }
+            }
+            Verdict::Error(error) => println!("    error: {error}"),
+            Verdict::StepLimit => println!("    step limit exceeded"),
+            Verdict::MemoryLimit => println!("    memory limit exceeded"),
+        }
+    }
+
+    let passed = outcomes.iter().filter(|outcome| outcome.passed()).count();
+    println!("\n{passed} passed, {} failed", outcomes.len() - passed);
+}
+
+fn print_json(outcomes: &[Outcome]) -> Result<()> {
+    let passed = outcomes.iter().filter(|outcome| outcome.passed()).count();
+    let results = outcomes
+        .iter()
+        .map(|outcome| {
+            json!({
+                "program": outcome.name,
+                "case": outcome.case,
+                "verdict": outcome.verdict.name(),
+                "error": match &outcome.verdict {
+                    Verdict::Error(error) => Some(error.to_string()),
+                    _ => None,
+                },
+                "diff": match &outcome.verdict {
+                    Verdict::Fail { diff } => Some(diff),
+                    _ => None,
+                },
+                "output": outcome.output,
+                "steps": outcome.steps,
+                "duration_ms": outcome.duration.as_secs_f64() * 1000.0,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let report = json!({
+        "passed": passed,
+        "failed": outcomes.len() - passed,
+        "results": results,
+    });
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use anyhow::{Context, Result};
+use std::{path::Path, process::ExitCode};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The file path
+    path: Box<Path>,
+
+    #[command(flatten)]
+    source: crate::source::Options,
+
+    /// The format to export the graph in
+    #[arg(short, long, value_enum, default_value = "dot")]
+    format: Format,
+
+    /// Write the graph to a file instead of stdout
+    #[arg(short, long)]
+    output: Option<Box<Path>>,
+}
+
+#[derive(Debug, Copy, Clone, clap::ValueEnum)]
+enum Format {
+    Dot,
+    Json,
+}
+
+pub async fn run(args: Args) -> Result<ExitCode> {
+    let program = crate::source::program(&args.path, &args.source).await?;
+    let graph = cowlang::cfg::build(&program);
+
+    let exported = match args.format {
+        Format::Dot => graph.to_dot(),
+        Format::Json => graph.to_json(),
+    };
+
+    match &args.output {
+        Some(path) => tokio::fs::write(path, exported)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => print!("{exported}"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,51 @@
+use anyhow::{Context, Result};
+use std::{
+    io::{self, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The file path
+    path: Box<Path>,
+
+    #[command(flatten)]
+    source: crate::source::Options,
+
+    /// The language to compile to
+    #[arg(short, long, value_enum)]
+    target: Target,
+
+    /// Write the output to a file instead of stdout
+    #[arg(short, long)]
+    output: Option<Box<Path>>,
+}
+
+#[derive(Debug, Copy, Clone, clap::ValueEnum)]
+enum Target {
+    C,
+    Rust,
+    Wasm,
+}
+
+pub async fn run(args: Args) -> Result<ExitCode> {
+    let program = crate::source::program(&args.path, &args.source).await?;
+
+    let compiled = match args.target {
+        Target::C => cowlang::backend::c::compile(&program).into_bytes(),
+        Target::Rust => cowlang::backend::rust::compile(&program).into_bytes(),
+        Target::Wasm => cowlang::backend::wasm::compile(&program),
+    };
+
+    match &args.output {
+        Some(path) => tokio::fs::write(path, compiled)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => io::stdout()
+            .write_all(&compiled)
+            .context("failed to write to stdout")?,
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,41 @@
+use crate::source::Kind;
+use anyhow::{Context, Result};
+use cowlang::binary;
+use std::{
+    io::{self, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The file path, of either source or an encoded program
+    path: Box<Path>,
+
+    #[command(flatten)]
+    source: crate::source::Options,
+
+    /// Write the converted program to a file instead of stdout
+    #[arg(short, long)]
+    output: Option<Box<Path>>,
+}
+
+pub async fn run(args: Args) -> Result<ExitCode> {
+    let (program, kind) = crate::source::load(&args.path, &args.source).await?;
+
+    let converted = match kind {
+        Kind::Encoded => cowlang::format::program(&program, &Default::default()).into_bytes(),
+        Kind::Plain | Kind::Preprocessed(_) => binary::encode(&program),
+    };
+
+    match &args.output {
+        Some(path) => tokio::fs::write(path, converted)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => io::stdout()
+            .write_all(&converted)
+            .context("failed to write to stdout")?,
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
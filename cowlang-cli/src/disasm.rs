@@ -0,0 +1,18 @@
+use anyhow::Result;
+use std::{path::Path, process::ExitCode};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The file path
+    path: Box<Path>,
+
+    #[command(flatten)]
+    source: crate::source::Options,
+}
+
+pub async fn run(args: Args) -> Result<ExitCode> {
+    let program = crate::source::program(&args.path, &args.source).await?;
+
+    print!("{}", cowlang::disasm::listing(&program));
+    Ok(ExitCode::SUCCESS)
+}
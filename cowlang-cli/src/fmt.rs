@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    process::ExitCode,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The file paths
+    #[arg(required = true)]
+    paths: Vec<Box<Path>>,
+
+    /// Report files that aren't formatted instead of rewriting them
+    #[arg(long)]
+    check: bool,
+
+    /// The most commands to put on one line
+    #[arg(short, long)]
+    words_per_line: Option<usize>,
+
+    /// The number of spaces to indent loop bodies by
+    #[arg(short, long)]
+    indent: Option<usize>,
+}
+
+pub async fn run(args: Args) -> Result<ExitCode> {
+    let defaults = cowlang::format::Options::default();
+    let options = cowlang::format::Options {
+        words_per_line: args.words_per_line.unwrap_or(defaults.words_per_line),
+        indent: args.indent.unwrap_or(defaults.indent),
+    };
+
+    let mut unformatted = 0;
+
+    for path in &args.paths {
+        let owned = path.to_path_buf();
+        let (formatted, unchanged) = tokio::task::spawn_blocking(move || {
+            let formatted = cowlang::format::read(BufReader::new(File::open(&owned)?), &options)?;
+            let unchanged = holds(&owned, formatted.as_bytes())?;
+            io::Result::Ok((formatted, unchanged))
+        })
+        .await?
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+        if unchanged {
+            continue;
+        }
+
+        if args.check {
+            println!("{} is not formatted", path.display());
+            unformatted += 1;
+        } else {
+            tokio::fs::write(path, formatted)
+                .await
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+    }
+
+    Ok(if unformatted > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Whether the file at `path` holds exactly `expected`, comparing a buffer at
+/// a time.
+fn holds(path: &Path, expected: &[u8]) -> io::Result<bool> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut rest = expected;
+
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(rest.is_empty());
+        }
+        let Some(tail) = rest.strip_prefix(buf) else {
+            return Ok(false);
+        };
+
+        rest = tail;
+        let len = buf.len();
+        reader.consume(len);
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+use cowlang::generate::Style;
+use std::{path::Path, process::ExitCode};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The text to print
+    text: String,
+
+    /// Favour a readable program over a short one
+    #[arg(short, long)]
+    readable: bool,
+
+    /// Write the COW source to a file instead of stdout
+    #[arg(short, long)]
+    output: Option<Box<Path>>,
+}
+
+pub async fn run(args: Args) -> Result<ExitCode> {
+    let style = if args.readable {
+        Style::Readable
+    } else {
+        Style::Compact
+    };
+
+    let program = cowlang::generate::print(&args.text, style)?;
+    let source = cowlang::format::program(&program, &Default::default());
+
+    match &args.output {
+        Some(path) => tokio::fs::write(path, source)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => print!("{source}"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
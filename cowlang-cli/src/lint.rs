@@ -0,0 +1,56 @@
+use crate::source::Kind;
+use anyhow::Result;
+use std::{path::Path, process::ExitCode};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The file paths
+    #[arg(required = true)]
+    paths: Vec<Box<Path>>,
+
+    #[command(flatten)]
+    source: crate::source::Options,
+}
+
+pub async fn run(args: Args) -> Result<ExitCode> {
+    let mut warnings = 0;
+
+    for path in &args.paths {
+        let (program, kind) = crate::source::load(path, &args.source).await?;
+        let mut lints = cowlang::lint::lint(&program);
+        lints.sort_by_key(|lint| lint.idx);
+
+        let idxs = lints.iter().map(|lint| lint.idx).collect::<Vec<_>>();
+        let locations = match &kind {
+            Kind::Plain => crate::source::spans(path, &idxs)
+                .await
+                .into_iter()
+                .map(|span| {
+                    span.map(|span| format!("{}:{}:{}", path.display(), span.line, span.column))
+                })
+                .collect(),
+            Kind::Encoded => vec![None; idxs.len()],
+            Kind::Preprocessed(source_map) => idxs
+                .iter()
+                .map(|&idx| {
+                    let origin = source_map.origin(idx)?;
+                    Some(source_map.describe(origin.location))
+                })
+                .collect(),
+        };
+
+        for (lint, location) in lints.iter().zip(locations) {
+            match location {
+                Some(location) => println!("{location}: {}", lint.kind),
+                None => println!("{}: command {}: {}", path.display(), lint.idx, lint.kind),
+            }
+            warnings += 1;
+        }
+    }
+
+    Ok(if warnings > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
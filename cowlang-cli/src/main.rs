@@ -1,28 +1,144 @@
+mod asm;
+mod batch;
+mod cfg;
+mod compile;
+mod convert;
+mod disasm;
+mod fmt;
+mod generate;
+mod lint;
+mod optimize;
+mod script;
+mod source;
+mod test;
+
 use anyhow::Result;
-use clap::Parser;
-use std::path::Path;
+use clap::{Parser, Subcommand};
+use cowlang::{Cowlang, Dialect, Reader, Writer};
+use source::Kind;
+use std::{io, path::Path, process::ExitCode};
 
 #[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
     /// The file path
-    path: Box<Path>,
+    #[arg(required = true)]
+    path: Option<Box<Path>>,
 
     /// Run the cowlang vizualizer
     #[arg(short, long)]
     vizualize: bool,
+
+    #[command(flatten)]
+    source: source::Options,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Assemble a program to COW source
+    Asm(asm::Args),
+
+    /// Run programs against test cases in parallel
+    Batch(batch::Args),
+
+    /// Export the control-flow graph of a program
+    Cfg(cfg::Args),
+
+    /// Compile a program to another language
+    Compile(compile::Args),
+
+    /// Convert a program between source and its binary encoding
+    Convert(convert::Args),
+
+    /// Print a readable listing of a program
+    Disasm(disasm::Args),
+
+    /// Format program source
+    Fmt(fmt::Args),
+
+    /// Generate a program that prints some text
+    Generate(generate::Args),
+
+    /// Warn about likely bugs in programs
+    Lint(lint::Args),
+
+    /// Shorten a program without changing what it does
+    Optimize(optimize::Args),
+
+    /// Compile a script to COW source
+    Script(script::Args),
+
+    /// Check sample programs against their expected output
+    Test(test::Args),
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     let cli = Cli::parse();
-    let text = tokio::fs::read_to_string(&cli.path).await?;
-    let program = cowlang::Program::parse(&text);
 
-    if cli.vizualize {
+    match cli.command {
+        Some(Commands::Asm(args)) => asm::run(args).await,
+        Some(Commands::Batch(args)) => batch::run(args).await,
+        Some(Commands::Cfg(args)) => cfg::run(args).await,
+        Some(Commands::Compile(args)) => compile::run(args).await,
+        Some(Commands::Convert(args)) => convert::run(args).await,
+        Some(Commands::Disasm(args)) => disasm::run(args).await,
+        Some(Commands::Fmt(args)) => fmt::run(args).await,
+        Some(Commands::Generate(args)) => generate::run(args).await,
+        Some(Commands::Lint(args)) => lint::run(args).await,
+        Some(Commands::Optimize(args)) => optimize::run(args).await,
+        Some(Commands::Script(args)) => script::run(args).await,
+        Some(Commands::Test(args)) => test::run(args).await,
+        None => {
+            let path = cli.path.expect("path is required without a subcommand");
+            run(&path, &cli.source, cli.vizualize).await
+        }
+    }
+}
+
+async fn run(path: &Path, options: &source::Options, vizualize: bool) -> Result<ExitCode> {
+    let (program, kind) = source::load(path, options).await?;
+
+    if vizualize {
         cowlang_viz::vizualize(cowlang_viz::Options { program }).await?;
-    } else {
-        todo!()
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let mut interp = Cowlang::new(cowlang::Options {
+        program,
+        input: Reader::new(io::stdin().lock()),
+        output: Writer::new(io::stdout()),
+        dialect: Dialect::REFERENCE,
+    });
+
+    let Err(error) = interp.run() else {
+        return Ok(ExitCode::SUCCESS);
+    };
+
+    eprintln!("error: {error}");
+    match kind {
+        Kind::Plain => {
+            if let [Some(span)] = source::spans(path, &[interp.program_idx()]).await[..] {
+                eprintln!("  at {}:{}:{}", path.display(), span.line, span.column);
+            }
+        }
+        Kind::Encoded => {}
+        Kind::Preprocessed(source_map) => {
+            if let Some(origin) = source_map.origin(interp.program_idx()) {
+                eprintln!("  at {}", source_map.describe(origin.location));
+                for expansion in origin.expansions.iter().rev() {
+                    eprintln!(
+                        "  in @{} at {}",
+                        expansion.name,
+                        source_map.describe(expansion.location)
+                    );
+                }
+            }
+        }
     }
 
-    Ok(())
+    Ok(ExitCode::FAILURE)
 }
@@ -0,0 +1,47 @@
+use anyhow::{Context, Result};
+use std::{path::Path, process::ExitCode};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The file path
+    path: Box<Path>,
+
+    #[command(flatten)]
+    source: crate::source::Options,
+
+    /// Remove `MoO MOo` pairs, which only differ from nothing at the largest value
+    #[arg(long)]
+    cancel_increments: bool,
+
+    /// Write the COW source to a file instead of stdout
+    #[arg(short, long)]
+    output: Option<Box<Path>>,
+}
+
+pub async fn run(args: Args) -> Result<ExitCode> {
+    let program = crate::source::program(&args.path, &args.source).await?;
+    let options = cowlang::optimize::Options {
+        cancel_increments: args.cancel_increments,
+    };
+
+    let optimized = cowlang::optimize::optimize(&program, &options);
+    let source = cowlang::format::program(&optimized.program, &Default::default());
+
+    for transformation in &optimized.transformations {
+        eprintln!("{transformation}");
+    }
+    eprintln!(
+        "{} commands, down from {}",
+        optimized.program.len(),
+        program.len()
+    );
+
+    match &args.output {
+        Some(path) => tokio::fs::write(path, source)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => print!("{source}"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,31 @@
+use anyhow::{Context, Result, bail};
+use std::{path::Path, process::ExitCode};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The script file path
+    path: Box<Path>,
+
+    /// Write the COW source to a file instead of stdout
+    #[arg(short, long)]
+    output: Option<Box<Path>>,
+}
+
+pub async fn run(args: Args) -> Result<ExitCode> {
+    let text = crate::source::text(&args.path).await?;
+
+    let program = match cowlang::script::compile(&text) {
+        Ok(program) => program,
+        Err(error) => bail!("{}:{error}", args.path.display()),
+    };
+    let source = cowlang::format::program(&program, &Default::default());
+
+    match &args.output {
+        Some(path) => tokio::fs::write(path, source)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => print!("{source}"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
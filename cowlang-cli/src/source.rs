@@ -0,0 +1,115 @@
+use anyhow::{Context, Result};
+use cowlang::{
+    Program, binary,
+    parse::{self, Span},
+    preprocess::{self, FileLoader, SourceMap},
+};
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+/// Reads the whole text at `path`, for languages whose parsers need all of it.
+pub async fn text(path: &Path) -> Result<String> {
+    let owned = path.to_path_buf();
+    tokio::task::spawn_blocking(move || io::read_to_string(BufReader::new(File::open(owned)?)))
+        .await?
+        .with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// How program files are read, by every subcommand that takes a program.
+#[derive(Debug, Clone, clap::Args)]
+pub struct Options {
+    /// Expand `#include`s and macros before reading the program
+    #[arg(long)]
+    pub preprocess: bool,
+}
+
+/// How a program was stored.
+#[derive(Debug)]
+pub enum Kind {
+    /// Plain COW source, parsed as it was read.
+    Plain,
+    /// A binary encoding.
+    Encoded,
+    /// Source using the preprocessor, with where its commands came from.
+    Preprocessed(SourceMap),
+}
+
+/// Reads the program at `path`.
+///
+/// Encoded programs are recognized by their header. Other files are plain COW
+/// source, parsed as they are read, unless `options` ask for preprocessing.
+pub async fn load(path: &Path, options: &Options) -> Result<(Program<'static>, Kind)> {
+    let owned = path.to_path_buf();
+    let preprocess = options.preprocess;
+    tokio::task::spawn_blocking(move || load_blocking(&owned, preprocess)).await?
+}
+
+/// Reads the program at `path`, like [`load`].
+pub async fn program(path: &Path, options: &Options) -> Result<Program<'static>> {
+    Ok(load(path, options).await?.0)
+}
+
+fn load_blocking(path: &Path, preprocess: bool) -> Result<(Program<'static>, Kind)> {
+    let context = || format!("failed to read {}", path.display());
+    let open = || File::open(path).map(BufReader::new).with_context(context);
+
+    let mut head = Vec::new();
+    open()?
+        .take(binary::MAGIC.len() as u64)
+        .read_to_end(&mut head)
+        .with_context(context)?;
+
+    if binary::is_encoded(&head) {
+        let program =
+            binary::load(path).with_context(|| format!("failed to decode {}", path.display()))?;
+        return Ok((program, Kind::Encoded));
+    }
+
+    if !preprocess {
+        let program = Program::read(open()?).with_context(context)?;
+        return Ok((program, Kind::Plain));
+    }
+
+    let bytes = std::fs::read(path).with_context(context)?;
+    let text =
+        String::from_utf8(bytes).with_context(|| format!("{} is not UTF-8", path.display()))?;
+    let preprocessed = preprocess::preprocess(&path.display().to_string(), &text, &mut FileLoader)?;
+    Ok((
+        preprocessed.program,
+        Kind::Preprocessed(preprocessed.source_map),
+    ))
+}
+
+/// Finds where each command at `idxs`, in ascending order, of the plain source
+/// at `path` was written, reading it again.
+pub async fn spans(path: &Path, idxs: &[usize]) -> Vec<Option<Span>> {
+    let owned = path.to_path_buf();
+    let wanted = idxs.to_vec();
+    let found = tokio::task::spawn_blocking(move || {
+        let mut found = vec![None; wanted.len()];
+        let Ok(file) = File::open(owned) else {
+            return found;
+        };
+
+        let mut wanted = wanted.iter().enumerate().peekable();
+        for (idx, spanned) in parse::read(BufReader::new(file)).enumerate() {
+            let Ok(spanned) = spanned else {
+                break;
+            };
+            while let Some((slot, _)) = wanted.next_if(|&(_, &target)| target == idx) {
+                found[slot] = Some(spanned.span);
+            }
+            if wanted.peek().is_none() {
+                break;
+            }
+        }
+
+        found
+    })
+    .await;
+
+    found.unwrap_or_else(|_| vec![None; idxs.len()])
+}
@@ -0,0 +1,29 @@
+use crate::batch::Limits;
+use anyhow::{Context, Result};
+use std::{path::Path, process::ExitCode};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// A directory of `<name>.txt` programs with `<name>.in` and `<name>.out`
+    /// files alongside
+    #[arg(default_value = "samples")]
+    dir: Box<Path>,
+
+    #[command(flatten)]
+    limits: Limits,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+pub async fn run(args: Args) -> Result<ExitCode> {
+    let options = args.limits.options();
+    let dir = args.dir.clone();
+
+    let outcomes = tokio::task::spawn_blocking(move || cowlang::golden::run(&dir, &options))
+        .await?
+        .with_context(|| format!("failed to read {}", args.dir.display()))?;
+
+    crate::batch::report(&outcomes, args.json)
+}
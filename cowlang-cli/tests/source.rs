@@ -0,0 +1,64 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn write(name: &str, source: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("cowlang-cli-{}-{name}", std::process::id()));
+    std::fs::write(&path, source).unwrap();
+    path
+}
+
+/// Runs the CLI with `args` followed by `path`, returning what it printed and
+/// whether it succeeded.
+fn cli(args: &[&str], path: &Path) -> (String, bool) {
+    let output = Command::new(env!("CARGO_BIN_EXE_cowlang-cli"))
+        .args(args)
+        .arg(path)
+        .output()
+        .unwrap();
+
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        output.status.success(),
+    )
+}
+
+/// The commands in a listing printed by `disasm`.
+fn listed(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().nth(2).unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn plain_comments_may_use_preprocessor_syntax() {
+    let path = write(
+        "plain.cow",
+        "MoO MoO OOM, then stop\nMoO OOM costs $5 (maybe)\n@home #1\n",
+    );
+
+    assert_eq!(cli(&[], &path), ("3".to_string(), true));
+
+    let (listing, success) = cli(&["disasm"], &path);
+    assert!(success);
+    assert_eq!(listed(&listing), ["MoO", "MoO", "MoO", "OOM"]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn preprocessing_is_asked_for() {
+    let path = write("macros.cow", "#define two MoO*2\n@two OOM\n");
+
+    assert_eq!(cli(&[], &path), ("0".to_string(), true));
+    assert_eq!(cli(&["--preprocess"], &path), ("2".to_string(), true));
+
+    let (listing, success) = cli(&["disasm", "--preprocess"], &path);
+    assert!(success);
+    assert_eq!(listed(&listing), ["MoO", "MoO", "OOM"]);
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -25,10 +25,11 @@ pub async fn vizualize<'a>(options: Options<'a>) -> Result<()> {
 
     let mut input = crate::io::InputTemp;
 
-    let interp = Cowlang::new(cowlang::Options {
+    let interp: Cowlang = Cowlang::new(cowlang::Options {
         program: options.program,
         input: &mut input,
         output: &mut output_tx,
+        dialect: cowlang::Dialect::REFERENCE,
     });
 
     let app = App {
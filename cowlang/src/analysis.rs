@@ -0,0 +1,489 @@
+//! Abstract interpretation of the cursor and cell values.
+//!
+//! Every path a program may take is followed at once, with jumps matched the
+//! way the interpreter matches them, until what may hold before each command
+//! stops changing. The cursor is tracked as an offset from the first cell,
+//! exact until paths disagree on it and a lower bound after that, and each
+//! cell as a known value, nonzero, or anything. Input may produce any value.
+
+use crate::{Command, Program, jump};
+use std::collections::{BTreeMap, BTreeSet};
+
+impl Program<'_> {
+    /// Analyses every path the program may take.
+    pub fn analyze(&self) -> Analysis {
+        Analysis::new(self)
+    }
+}
+
+/// What a cell may hold.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Known(u32),
+    NonZero,
+    Unknown,
+}
+
+impl Cell {
+    pub fn is_zero(self) -> bool {
+        self == Cell::Known(0)
+    }
+
+    pub fn is_nonzero(self) -> bool {
+        match self {
+            Cell::Known(value) => value != 0,
+            Cell::NonZero => true,
+            Cell::Unknown => false,
+        }
+    }
+
+    pub fn may_be(self, value: u32) -> bool {
+        match self {
+            Cell::Known(known) => known == value,
+            Cell::NonZero => value != 0,
+            Cell::Unknown => true,
+        }
+    }
+
+    pub fn may_exceed(self, value: u32) -> bool {
+        match self {
+            Cell::Known(known) => known > value,
+            Cell::NonZero | Cell::Unknown => true,
+        }
+    }
+
+    fn join(self, other: Cell) -> Cell {
+        match (self, other) {
+            _ if self == other => self,
+            _ if self.is_nonzero() && other.is_nonzero() => Cell::NonZero,
+            _ => Cell::Unknown,
+        }
+    }
+}
+
+/// What may hold just before a command runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Point {
+    /// The lowest offset the cursor may be at.
+    min_offset: usize,
+    exact: bool,
+    /// Cells written while the cursor was exact.
+    cells: BTreeMap<usize, Cell>,
+    /// Every cell not in `cells`.
+    rest: Cell,
+    current: Cell,
+    register_empty: bool,
+    /// What the register may hold and the `MMM`s that may have filled it.
+    register_full: Option<(Cell, BTreeSet<usize>)>,
+}
+
+impl Point {
+    fn new() -> Self {
+        Self {
+            min_offset: 0,
+            exact: true,
+            cells: BTreeMap::new(),
+            rest: Cell::Known(0),
+            current: Cell::Known(0),
+            register_empty: true,
+            register_full: None,
+        }
+    }
+
+    /// The offset of the cursor from the first cell, if every path agrees.
+    pub fn offset(&self) -> Option<usize> {
+        self.exact.then_some(self.min_offset)
+    }
+
+    /// The lowest offset the cursor may be at.
+    pub fn min_offset(&self) -> usize {
+        self.min_offset
+    }
+
+    /// What the cell under the cursor may hold.
+    pub fn current(&self) -> Cell {
+        self.current
+    }
+
+    /// What the cell at `offset` may hold.
+    pub fn cell(&self, offset: usize) -> Cell {
+        self.cells.get(&offset).copied().unwrap_or(self.rest)
+    }
+
+    /// What a `mOO` run here may evaluate.
+    pub fn dispatch(&self) -> Dispatch {
+        let commands = (0..=Command::oom as u32)
+            .filter(|&value| value != Command::mOO as u32 && self.current.may_be(value))
+            .map(|value| Command::try_from(value).unwrap())
+            .collect();
+
+        Dispatch {
+            commands,
+            may_recurse: self.current.may_be(Command::mOO as u32),
+            may_be_invalid: self.current.may_exceed(Command::oom as u32),
+        }
+    }
+
+    pub fn register_may_be_full(&self) -> bool {
+        self.register_full.is_some()
+    }
+
+    /// The `MMM`s that may have left their value in the register.
+    pub(crate) fn register_filled_by(&self) -> impl Iterator<Item = usize> + '_ {
+        self.register_full
+            .iter()
+            .flat_map(|(_, filled_by)| filled_by.iter().copied())
+    }
+
+    fn join(&self, other: &Point) -> Point {
+        let cells = self
+            .cells
+            .keys()
+            .chain(other.cells.keys())
+            .map(|&offset| (offset, self.cell(offset).join(other.cell(offset))))
+            .collect();
+
+        let register_full = match (&self.register_full, &other.register_full) {
+            (Some((a, a_by)), Some((b, b_by))) => {
+                Some((a.join(*b), a_by.union(b_by).copied().collect()))
+            }
+            (full, None) | (None, full) => full.clone(),
+        };
+
+        Point {
+            min_offset: self.min_offset.min(other.min_offset),
+            exact: self.offset().is_some() && self.offset() == other.offset(),
+            cells,
+            rest: self.rest.join(other.rest),
+            current: self.current.join(other.current),
+            register_empty: self.register_empty || other.register_empty,
+            register_full,
+        }
+    }
+
+    fn move_to(&mut self, min_offset: usize) {
+        self.min_offset = min_offset;
+        self.current = match self.offset() {
+            Some(offset) => self.cell(offset),
+            None => self
+                .cells
+                .range(min_offset..)
+                .fold(self.rest, |acc, (_, &cell)| acc.join(cell)),
+        };
+    }
+
+    /// Narrows what the current value may be, on a path where that is known.
+    fn refine(&mut self, cell: Cell) {
+        self.current = cell;
+        if let Some(offset) = self.offset() {
+            self.cells.insert(offset, cell);
+        }
+    }
+
+    fn write(&mut self, cell: Cell) {
+        self.current = cell;
+        match self.offset() {
+            Some(offset) => {
+                self.cells.insert(offset, cell);
+            }
+            // any cell the cursor may be on may be the one written
+            None => {
+                for (_, existing) in self.cells.range_mut(self.min_offset..) {
+                    *existing = existing.join(cell);
+                }
+                self.rest = self.rest.join(cell);
+            }
+        }
+    }
+}
+
+/// The commands a `mOO` may run, and whether it may end the program or fail
+/// instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dispatch {
+    /// In command number order, never including `mOO`.
+    pub commands: Vec<Command>,
+    /// Whether it may evaluate 3, which ends the program.
+    pub may_recurse: bool,
+    /// Whether it may evaluate a value above 11, which is an error.
+    pub may_be_invalid: bool,
+}
+
+impl Dispatch {
+    /// The command run whenever the `mOO` is, if it can only run one and
+    /// never ends the program or fails.
+    pub fn only(&self) -> Option<Command> {
+        match self.commands[..] {
+            [command] if !self.may_recurse && !self.may_be_invalid => Some(command),
+            _ => None,
+        }
+    }
+}
+
+/// A `MOO` and `moo` that jump to each other.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Loop {
+    pub start: usize,
+    pub end: usize,
+    /// How far each pass through the body from the `MOO` moves the cursor,
+    /// if that is the same every time.
+    pub shift: Option<isize>,
+}
+
+impl Loop {
+    /// Whether each pass through the body leaves the cursor where it started.
+    pub fn is_balanced(&self) -> bool {
+        self.shift == Some(0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    /// What may hold before each command runs, and at exit.
+    points: Vec<Option<Point>>,
+    loops: Vec<Loop>,
+}
+
+impl Analysis {
+    fn new(program: &[Command]) -> Self {
+        let mut analysis = Self {
+            points: vec![None; program.len() + 1],
+            loops: Vec::new(),
+        };
+        analysis.run(program);
+
+        for (start, &command) in program.iter().enumerate() {
+            if command == Command::MOO
+                && let Some(end) = matched_end(program, start)
+            {
+                let shift = analysis.shift(program, start, end);
+                analysis.loops.push(Loop { start, end, shift });
+            }
+        }
+
+        analysis
+    }
+
+    /// What may hold before the command at `idx` runs, or `None` if it never
+    /// does.
+    pub fn point(&self, idx: usize) -> Option<&Point> {
+        self.points.get(idx)?.as_ref()
+    }
+
+    /// What may hold when the program ends, or `None` if it never does.
+    pub fn exit(&self) -> Option<&Point> {
+        self.points.last()?.as_ref()
+    }
+
+    pub fn is_reachable(&self, idx: usize) -> bool {
+        self.point(idx).is_some()
+    }
+
+    /// Every loop whose `MOO` and `moo` jump to each other, by `MOO`.
+    pub fn loops(&self) -> &[Loop] {
+        &self.loops
+    }
+
+    /// Whether the cell at `offset` holds 0 before the command at `idx`
+    /// runs, on every path that gets there.
+    pub fn is_zero(&self, idx: usize, offset: usize) -> bool {
+        self.point(idx)
+            .is_some_and(|point| point.cell(offset).is_zero())
+    }
+
+    fn run(&mut self, program: &[Command]) {
+        self.points[0] = Some(Point::new());
+        let mut pending = BTreeSet::from([0]);
+
+        while let Some(idx) = pending.pop_first() {
+            let Some(point) = self.points[idx].clone() else {
+                continue;
+            };
+            if idx == program.len() {
+                continue;
+            }
+
+            let mut next = Vec::new();
+            step(program, idx, program[idx], point, &mut next);
+
+            for (idx, point) in next {
+                let joined = match &self.points[idx] {
+                    Some(existing) => {
+                        let mut joined = existing.join(&point);
+                        // stop a loop walking the bound down one cell at a time
+                        if existing.offset().is_none() && joined.min_offset < existing.min_offset {
+                            joined.min_offset = 0;
+                        }
+                        joined
+                    }
+                    None => point,
+                };
+
+                if self.points[idx].as_ref() != Some(&joined) {
+                    self.points[idx] = Some(joined);
+                    pending.insert(idx);
+                }
+            }
+        }
+    }
+
+    /// Follows the body of the loop from `start` to `end`, counting moves.
+    /// Nested loops must be balanced, and a `mOO` must only run one command.
+    fn shift(&self, program: &[Command], start: usize, end: usize) -> Option<isize> {
+        let mut shift = 0isize;
+        let mut idx = start + 1;
+
+        while idx < end {
+            let point = self.point(idx);
+            let command = match program[idx] {
+                Command::mOO => match point?.dispatch().only()? {
+                    Command::MOO | Command::moo => return None,
+                    command => command,
+                },
+                command => command,
+            };
+
+            match command {
+                Command::moO => shift += 1,
+                Command::mOo => {
+                    let point = point?;
+                    if point.min_offset() > 0 {
+                        shift -= 1;
+                    } else if point.offset() != Some(0) {
+                        return None;
+                    }
+                }
+                Command::MOO => {
+                    let inner = matched_end(program, idx).filter(|&inner| inner < end)?;
+                    if self.shift(program, idx, inner) != Some(0) {
+                        return None;
+                    }
+                    idx = inner;
+                }
+                Command::moo => return None,
+                _ => {}
+            }
+
+            idx += 1;
+        }
+
+        Some(shift)
+    }
+}
+
+/// The `moo` matching the `MOO` at `start`, if it also jumps back there.
+fn matched_end(program: &[Command], start: usize) -> Option<usize> {
+    jump::forward(program, start).filter(|&end| jump::backward(program, end) == Some(start))
+}
+
+/// Runs `command` as if it were at `idx`, which differs from the command there
+/// when a `mOO` evaluates it, pushing where execution may continue.
+fn step(
+    program: &[Command],
+    idx: usize,
+    command: Command,
+    mut point: Point,
+    next: &mut Vec<(usize, Point)>,
+) {
+    match command {
+        // the `MOO` jumped to is evaluated again
+        Command::moo => {
+            if let Some(start) = jump::backward(program, idx) {
+                next.push((start, point));
+            }
+            return;
+        }
+        Command::MOO => {
+            branch(program, idx, point, next);
+            return;
+        }
+        Command::mOo => {
+            let offset = point.min_offset.saturating_sub(1);
+            point.move_to(offset);
+        }
+        Command::moO => {
+            let offset = point.min_offset.saturating_add(1);
+            point.move_to(offset);
+        }
+        Command::mOO => {
+            let dispatch = point.dispatch();
+            // evaluating itself ends the program
+            if dispatch.may_recurse {
+                let mut exit = point.clone();
+                exit.refine(Cell::Known(Command::mOO as u32));
+                next.push((program.len(), exit));
+            }
+            for command in dispatch.commands {
+                let mut point = point.clone();
+                point.refine(Cell::Known(command as u32));
+                step(program, idx, command, point, next);
+            }
+            return;
+        }
+        Command::Moo => {
+            if point.current.may_be(0) {
+                let mut read = point.clone();
+                read.write(Cell::Unknown);
+                next.push((idx + 1, read));
+            }
+            if point.current.is_zero() {
+                return;
+            }
+            if !point.current.is_nonzero() {
+                point.refine(Cell::NonZero);
+            }
+        }
+        Command::MOo => {
+            let cell = match point.current {
+                Cell::Known(value) => Cell::Known(value.saturating_sub(1)),
+                Cell::NonZero | Cell::Unknown => Cell::Unknown,
+            };
+            point.write(cell);
+        }
+        Command::MoO => {
+            let cell = match point.current {
+                Cell::Known(value) => Cell::Known(value.saturating_add(1)),
+                Cell::NonZero | Cell::Unknown => Cell::NonZero,
+            };
+            point.write(cell);
+        }
+        Command::OOO => point.write(Cell::Known(0)),
+        Command::MMM => {
+            if let Some((held, _)) = point.register_full.clone() {
+                let mut drained = point.clone();
+                drained.write(held);
+                drained.register_empty = true;
+                drained.register_full = None;
+                next.push((idx + 1, drained));
+            }
+            if !point.register_empty {
+                return;
+            }
+            point.register_empty = false;
+            point.register_full = Some((point.current, BTreeSet::from([idx])));
+        }
+        Command::OOM => {}
+        Command::oom => point.write(Cell::Unknown),
+    }
+
+    next.push((idx + 1, point));
+}
+
+/// Evaluates a `MOO` at `idx`.
+fn branch(program: &[Command], idx: usize, point: Point, next: &mut Vec<(usize, Point)>) {
+    if point.current.may_be(0)
+        && let Some(end) = jump::forward(program, idx)
+    {
+        let mut zero = point.clone();
+        zero.refine(Cell::Known(0));
+        next.push((end + 1, zero));
+    }
+
+    if !point.current.is_zero() {
+        let mut nonzero = point;
+        if !nonzero.current.is_nonzero() {
+            nonzero.refine(Cell::NonZero);
+        }
+        next.push((idx + 1, nonzero));
+    }
+}
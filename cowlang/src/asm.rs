@@ -0,0 +1,348 @@
+//! An assembly language that compiles to COW.
+//!
+//! Instructions are separated by whitespace, and `;` starts a comment that
+//! runs to the end of the line:
+//!
+//! | Instruction      | COW                                 |
+//! |------------------|-------------------------------------|
+//! | `add [n]`        | `MoO`, `n` times (1 by default)     |
+//! | `sub [n]`        | `MOo`, `n` times                    |
+//! | `right [n]`      | `moO`, `n` times                    |
+//! | `left [n]`       | `mOo`, `n` times                    |
+//! | `clear`          | `OOO`                               |
+//! | `swap`           | `MMM`                               |
+//! | `print`          | `OOM`                               |
+//! | `read`           | `oom`                               |
+//! | `char`           | `Moo`                               |
+//! | `eval`           | `mOO`                               |
+//! | `loop { … }`     | `MOO … moo`                         |
+//! | `raw <commands>` | the COW commands that follow        |
+//!
+//! `name:` labels the current cell, and `goto name` moves back to it. Labels
+//! rely on knowing where the cursor is, so they can't be used after `eval` or
+//! a loop that doesn't end where it started, or inside such a loop.
+//!
+//! Counts and `goto`s expand into one command per step, and may not take a
+//! program past [`MAX_LEN`] commands.
+
+use crate::{Builder, Command, Program};
+use std::{collections::HashMap, fmt};
+
+/// The most commands counts and `goto`s may expand a program to.
+pub const MAX_LEN: usize = 1 << 24;
+
+pub fn assemble(source: &str) -> Result<Program<'static>, Error> {
+    let tokens = tokenize(source);
+    let mut assembler = Assembler {
+        tokens: &tokens,
+        idx: 0,
+        builder: Builder::new(),
+        labels: HashMap::new(),
+        position: Some(0),
+        used_position: false,
+    };
+
+    assembler.block(None)?;
+    Ok(assembler.builder.build())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    UnknownInstruction(String),
+    InvalidCount(String),
+    ExpectedOpenBrace,
+    UnexpectedCloseBrace,
+    UnclosedLoop,
+    ExpectedCommand,
+    ExpectedLabel,
+    UnknownLabel(String),
+    DuplicateLabel(String),
+    UnknownPosition,
+    UnbalancedLoop,
+    /// A count or `goto` would take the program past [`MAX_LEN`] commands.
+    TooLong,
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+
+        match &self.kind {
+            ErrorKind::UnknownInstruction(word) => write!(f, "unknown instruction `{word}`"),
+            ErrorKind::InvalidCount(word) => write!(f, "invalid count `{word}`"),
+            ErrorKind::ExpectedOpenBrace => f.write_str("expected `{` after `loop`"),
+            ErrorKind::UnexpectedCloseBrace => f.write_str("unexpected `}`"),
+            ErrorKind::UnclosedLoop => f.write_str("unclosed loop"),
+            ErrorKind::ExpectedCommand => f.write_str("expected a COW command after `raw`"),
+            ErrorKind::ExpectedLabel => f.write_str("expected a label after `goto`"),
+            ErrorKind::UnknownLabel(label) => write!(f, "unknown label `{label}`"),
+            ErrorKind::DuplicateLabel(label) => write!(f, "label `{label}` is already defined"),
+            ErrorKind::UnknownPosition => {
+                f.write_str("the cursor position is not known here, so labels cannot be used")
+            }
+            ErrorKind::UnbalancedLoop => f.write_str(
+                "loop moves the cursor and uses labels, which would be wrong after the first pass",
+            ),
+            ErrorKind::TooLong => write!(f, "program is longer than {MAX_LEN} commands"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Token<'s> {
+    text: &'s str,
+    line: usize,
+    column: usize,
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+
+    for (line_idx, line) in source.lines().enumerate() {
+        let code = line.split(';').next().unwrap_or_default();
+        let mut start = None;
+
+        for (idx, char) in code.char_indices().chain([(code.len(), ' ')]) {
+            let brace = matches!(char, '{' | '}');
+
+            if (char.is_whitespace() || brace)
+                && let Some(start) = start.take()
+            {
+                tokens.push(Token {
+                    text: &code[start..idx],
+                    line: line_idx + 1,
+                    column: start + 1,
+                });
+            }
+
+            if brace {
+                tokens.push(Token {
+                    text: &code[idx..idx + 1],
+                    line: line_idx + 1,
+                    column: idx + 1,
+                });
+            } else if !char.is_whitespace() && start.is_none() {
+                start = Some(idx);
+            }
+        }
+    }
+
+    tokens
+}
+
+struct Assembler<'t, 's> {
+    tokens: &'t [Token<'s>],
+    idx: usize,
+    builder: Builder,
+    labels: HashMap<&'s str, usize>,
+    /// The cursor position, while it is known.
+    position: Option<usize>,
+    used_position: bool,
+}
+
+impl<'s> Assembler<'_, 's> {
+    /// Assembles instructions up to the `}` closing `open`, or to the end of
+    /// the source at the top level.
+    fn block(&mut self, open: Option<Token<'s>>) -> Result<(), Error> {
+        while let Some(token) = self.next() {
+            let (count, command) = match token.text {
+                "}" if open.is_some() => return Ok(()),
+                "}" => return Err(error(token, ErrorKind::UnexpectedCloseBrace)),
+                "add" => (self.count()?, Command::MoO),
+                "sub" => (self.count()?, Command::MOo),
+                "right" => {
+                    let count = self.count()?;
+                    self.position = self.position.map(|position| position + count);
+                    (count, Command::moO)
+                }
+                "left" => {
+                    let count = self.count()?;
+                    self.position = self.position.map(|position| position.saturating_sub(count));
+                    (count, Command::mOo)
+                }
+                "clear" => (1, Command::OOO),
+                "swap" => (1, Command::MMM),
+                "print" => (1, Command::OOM),
+                "read" => (1, Command::oom),
+                "char" => (1, Command::Moo),
+                "eval" => {
+                    self.position = None;
+                    (1, Command::mOO)
+                }
+                "loop" => {
+                    self.loop_(token)?;
+                    continue;
+                }
+                "raw" => {
+                    self.raw(token)?;
+                    continue;
+                }
+                "goto" => {
+                    self.goto(token)?;
+                    continue;
+                }
+                label if label.len() > 1 && label.ends_with(':') => {
+                    self.label(token, &label[..label.len() - 1])?;
+                    continue;
+                }
+                word => {
+                    let kind = ErrorKind::UnknownInstruction(word.to_string());
+                    return Err(error(token, kind));
+                }
+            };
+
+            self.builder.extend(std::iter::repeat_n(command, count));
+        }
+
+        match open {
+            Some(open) => Err(error(open, ErrorKind::UnclosedLoop)),
+            None => Ok(()),
+        }
+    }
+
+    fn loop_(&mut self, token: Token<'s>) -> Result<(), Error> {
+        let open = match self.next() {
+            Some(open) if open.text == "{" => open,
+            _ => return Err(error(token, ErrorKind::ExpectedOpenBrace)),
+        };
+
+        let start = self.position;
+        let used_position = std::mem::replace(&mut self.used_position, false);
+
+        self.builder.push(Command::MOO);
+        self.block(Some(open))?;
+        self.builder.push(Command::moo);
+
+        if self.position != start {
+            if self.used_position {
+                return Err(error(token, ErrorKind::UnbalancedLoop));
+            }
+            self.position = None;
+        }
+
+        self.used_position |= used_position;
+        Ok(())
+    }
+
+    fn raw(&mut self, token: Token<'s>) -> Result<(), Error> {
+        let mut any = false;
+
+        while let Some(command) = self
+            .peek()
+            .and_then(|next| next.text.parse::<Command>().ok())
+        {
+            self.idx += 1;
+            any = true;
+
+            match command {
+                Command::moO => self.position = self.position.map(|position| position + 1),
+                Command::mOo => {
+                    self.position = self.position.map(|position| position.saturating_sub(1))
+                }
+                Command::mOO | Command::MOO | Command::moo => self.position = None,
+                _ => {}
+            }
+
+            self.builder.push(command);
+        }
+
+        if any {
+            Ok(())
+        } else {
+            Err(error(token, ErrorKind::ExpectedCommand))
+        }
+    }
+
+    fn goto(&mut self, token: Token<'s>) -> Result<(), Error> {
+        let Some(label) = self.next() else {
+            return Err(error(token, ErrorKind::ExpectedLabel));
+        };
+        let Some(&target) = self.labels.get(label.text) else {
+            let kind = ErrorKind::UnknownLabel(label.text.to_string());
+            return Err(error(label, kind));
+        };
+        let Some(position) = self.position else {
+            return Err(error(token, ErrorKind::UnknownPosition));
+        };
+
+        self.reserve(token, target.abs_diff(position))?;
+        self.used_position = true;
+        self.position = Some(target);
+
+        if target > position {
+            self.builder
+                .extend(std::iter::repeat_n(Command::moO, target - position));
+        } else {
+            self.builder
+                .extend(std::iter::repeat_n(Command::mOo, position - target));
+        }
+        Ok(())
+    }
+
+    fn label(&mut self, token: Token<'s>, label: &'s str) -> Result<(), Error> {
+        let Some(position) = self.position else {
+            return Err(error(token, ErrorKind::UnknownPosition));
+        };
+
+        if self.labels.insert(label, position).is_some() {
+            return Err(error(token, ErrorKind::DuplicateLabel(label.to_string())));
+        }
+
+        self.used_position = true;
+        Ok(())
+    }
+
+    /// Takes an optional count following an instruction.
+    fn count(&mut self) -> Result<usize, Error> {
+        let Some(token) = self.peek() else {
+            return Ok(1);
+        };
+
+        if !token.text.starts_with(|char: char| char.is_ascii_digit()) {
+            return Ok(1);
+        }
+
+        self.idx += 1;
+        let count = token
+            .text
+            .parse()
+            .map_err(|_| error(token, ErrorKind::InvalidCount(token.text.to_string())))?;
+        self.reserve(token, count)?;
+        Ok(count)
+    }
+
+    /// Checks that `count` more commands keep the program within [`MAX_LEN`].
+    fn reserve(&self, token: Token<'s>, count: usize) -> Result<(), Error> {
+        if count > MAX_LEN.saturating_sub(self.builder.commands().len()) {
+            return Err(error(token, ErrorKind::TooLong));
+        }
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<Token<'s>> {
+        self.tokens.get(self.idx).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'s>> {
+        let token = self.peek()?;
+        self.idx += 1;
+        Some(token)
+    }
+}
+
+fn error(token: Token<'_>, kind: ErrorKind) -> Error {
+    Error {
+        line: token.line,
+        column: token.column,
+        kind,
+    }
+}
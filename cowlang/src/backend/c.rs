@@ -0,0 +1,313 @@
+//! Compiles a program to a standalone C source file.
+//!
+//! The generated program reads from stdin and writes to stdout the way
+//! [`Reader`](crate::Reader) and [`Writer`](crate::Writer) do, and exits with
+//! status 1 after printing the interpreter's error message when a program
+//! fails.
+
+use super::{Exit, Op, Target};
+use crate::Command;
+use std::{collections::HashSet, fmt::Write};
+
+const PRELUDE: &str = r#"#include <inttypes.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+static uint32_t *memory;
+static size_t memory_len = 1;
+static size_t memory_cap = 1024;
+static size_t memory_idx = 0;
+static uint32_t reg;
+static int reg_full = 0;
+
+static inline _Noreturn void fail(const char *message) {
+    fflush(stdout);
+    fprintf(stderr, "error: %s\n", message);
+    exit(1);
+}
+
+static inline void add(uint32_t n) {
+    uint32_t *value = &memory[memory_idx];
+    *value = UINT32_MAX - *value < n ? UINT32_MAX : *value + n;
+}
+
+static inline void sub(uint32_t n) {
+    uint32_t *value = &memory[memory_idx];
+    *value = *value < n ? 0 : *value - n;
+}
+
+static inline void right(uint32_t n) {
+    memory_idx += n;
+
+    if (memory_idx >= memory_cap) {
+        size_t cap = memory_cap;
+        while (memory_idx >= cap) {
+            cap *= 2;
+        }
+        memory = realloc(memory, cap * sizeof *memory);
+        if (memory == NULL) {
+            fail("out of memory");
+        }
+        memory_cap = cap;
+    }
+
+    if (memory_idx >= memory_len) {
+        memset(&memory[memory_len], 0, (memory_idx + 1 - memory_len) * sizeof *memory);
+        memory_len = memory_idx + 1;
+    }
+}
+
+static inline void left(uint32_t n) {
+    memory_idx = memory_idx < n ? 0 : memory_idx - n;
+}
+
+static inline void clear(void) {
+    memory[memory_idx] = 0;
+}
+
+static inline void reg_swap(void) {
+    if (reg_full) {
+        memory[memory_idx] = reg;
+        reg_full = 0;
+    } else {
+        reg = memory[memory_idx];
+        reg_full = 1;
+    }
+}
+
+static inline int read_byte(void) {
+    int byte = getchar();
+    if (byte == EOF) {
+        fail("failed to fill whole buffer");
+    }
+    return byte;
+}
+
+static inline uint32_t read_char(void) {
+    int byte = read_byte();
+    int len;
+    uint32_t value;
+    uint32_t min;
+
+    if (byte < 0x80) {
+        return (uint32_t)byte;
+    } else if (byte >= 0xC0 && byte < 0xE0) {
+        len = 2;
+        value = byte & 0x1F;
+        min = 0x80;
+    } else if (byte >= 0xE0 && byte < 0xF0) {
+        len = 3;
+        value = byte & 0x0F;
+        min = 0x800;
+    } else if (byte >= 0xF0 && byte < 0xF8) {
+        len = 4;
+        value = byte & 0x07;
+        min = 0x10000;
+    } else {
+        fail("invalid data");
+    }
+
+    int rest[3];
+    for (int i = 1; i < len; i++) {
+        rest[i - 1] = read_byte();
+    }
+
+    for (int i = 1; i < len; i++) {
+        if ((rest[i - 1] & 0xC0) != 0x80) {
+            fail("invalid data");
+        }
+        value = (value << 6) | (rest[i - 1] & 0x3F);
+    }
+
+    if (value < min || value > 0x10FFFF || (value >= 0xD800 && value <= 0xDFFF)) {
+        fail("invalid data");
+    }
+
+    return value;
+}
+
+static inline void write_char(uint32_t value) {
+    if (value > 0x10FFFF || (value >= 0xD800 && value <= 0xDFFF)) {
+        fail("unwritable char");
+    }
+
+    if (value < 0x80) {
+        putchar(value);
+    } else if (value < 0x800) {
+        putchar(0xC0 | (value >> 6));
+        putchar(0x80 | (value & 0x3F));
+    } else if (value < 0x10000) {
+        putchar(0xE0 | (value >> 12));
+        putchar(0x80 | ((value >> 6) & 0x3F));
+        putchar(0x80 | (value & 0x3F));
+    } else {
+        putchar(0xF0 | (value >> 18));
+        putchar(0x80 | ((value >> 12) & 0x3F));
+        putchar(0x80 | ((value >> 6) & 0x3F));
+        putchar(0x80 | (value & 0x3F));
+    }
+}
+
+static inline void moo(void) {
+    if (memory[memory_idx] == 0) {
+        memory[memory_idx] = read_char();
+    } else {
+        write_char(memory[memory_idx]);
+    }
+}
+
+static inline void output_int(void) {
+    printf("%" PRIu32, memory[memory_idx]);
+}
+
+static inline int is_space(char c) {
+    return c == ' ' || c == '\t' || c == '\r' || c == '\v' || c == '\f';
+}
+
+static inline void input_int(void) {
+    static char *line = NULL;
+    static size_t line_cap = 0;
+    size_t len = 0;
+    int byte = getchar();
+
+    if (byte == EOF) {
+        fail("unexpected end of file");
+    }
+
+    while (byte != EOF && byte != '\n') {
+        if (len == line_cap) {
+            line_cap = line_cap == 0 ? 64 : line_cap * 2;
+            line = realloc(line, line_cap);
+            if (line == NULL) {
+                fail("out of memory");
+            }
+        }
+        line[len++] = (char)byte;
+        byte = getchar();
+    }
+
+    size_t start = 0;
+    size_t end = len;
+    while (start < end && is_space(line[start])) {
+        start++;
+    }
+    while (end > start && is_space(line[end - 1])) {
+        end--;
+    }
+
+    if (start == end) {
+        fail("cannot parse integer from empty string");
+    }
+    if (line[start] == '+' && end - start > 1) {
+        start++;
+    }
+
+    uint64_t value = 0;
+    for (size_t i = start; i < end; i++) {
+        if (line[i] < '0' || line[i] > '9') {
+            fail("invalid digit found in string");
+        }
+        value = value * 10 + (uint64_t)(line[i] - '0');
+        if (value > UINT32_MAX) {
+            fail("number too large to fit in target type");
+        }
+    }
+
+    memory[memory_idx] = (uint32_t)value;
+}
+"#;
+
+pub fn compile(program: &[Command]) -> String {
+    let blocks = super::lower(program);
+    let targets = blocks
+        .iter()
+        .flat_map(|block| match block.exit {
+            Exit::Next => vec![],
+            Exit::Branch { zero: target } | Exit::Jump(target) => vec![target],
+            // a `mOO` evaluating itself ends the program
+            Exit::Eval { moo } => vec![moo, Target::End],
+        })
+        .collect::<HashSet<_>>();
+
+    let mut out = String::from(PRELUDE);
+
+    out.push_str("\nint main(void) {\n");
+    out.push_str("    memory = calloc(memory_cap, sizeof *memory);\n");
+    out.push_str("    if (memory == NULL) {\n        fail(\"out of memory\");\n    }\n");
+
+    for (id, block) in blocks.iter().enumerate() {
+        if targets.contains(&Target::Block(id)) {
+            writeln!(out, "\nb{id}:").unwrap();
+        } else {
+            out.push('\n');
+        }
+
+        for &op in &block.ops {
+            writeln!(out, "    {}", op_statement(op)).unwrap();
+        }
+
+        match block.exit {
+            Exit::Next => {}
+            Exit::Branch { zero } => {
+                writeln!(out, "    if (memory[memory_idx] == 0) {}", goto(zero)).unwrap();
+            }
+            Exit::Jump(target) => {
+                writeln!(out, "    {}", goto(target)).unwrap();
+            }
+            Exit::Eval { moo } => {
+                out.push_str("    switch (memory[memory_idx]) {\n");
+
+                for value in 0..12 {
+                    let command = Command::try_from(value).unwrap();
+                    let statement = match command {
+                        Command::moo => goto(moo),
+                        Command::mOO => goto(Target::End),
+                        Command::MOO => "break;".to_string(),
+                        command => {
+                            let op = Op::of(command).unwrap();
+                            format!("{} break;", op_statement(op))
+                        }
+                    };
+                    writeln!(out, "    case {value}: {statement}").unwrap();
+                }
+
+                writeln!(
+                    out,
+                    "    default: {}\n    }}",
+                    goto(Target::Trap(super::Trap::InvalidCommand))
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    if targets.contains(&Target::End) {
+        out.push_str("\nend:");
+    }
+    out.push_str("\n    fflush(stdout);\n    return 0;\n}\n");
+    out
+}
+
+fn op_statement(op: Op) -> String {
+    match op {
+        Op::Add(n) => format!("add({n});"),
+        Op::Sub(n) => format!("sub({n});"),
+        Op::Right(n) => format!("right({n});"),
+        Op::Left(n) => format!("left({n});"),
+        Op::Clear => "clear();".to_string(),
+        Op::Register => "reg_swap();".to_string(),
+        Op::Moo => "moo();".to_string(),
+        Op::OutputInt => "output_int();".to_string(),
+        Op::InputInt => "input_int();".to_string(),
+    }
+}
+
+fn goto(target: Target) -> String {
+    match target {
+        Target::Block(id) => format!("goto b{id};"),
+        Target::End => "goto end;".to_string(),
+        Target::Trap(trap) => format!("fail(\"{}\");", trap.message()),
+    }
+}
@@ -0,0 +1,225 @@
+//! Compilers from a COW program to other languages.
+//!
+//! Every backend works from the same lowering: the program is split into
+//! basic blocks of straight-line operations, each ending in an exit that
+//! mirrors how the interpreter moves between commands. Jump targets are
+//! resolved ahead of time with the interpreter's own matching rules, and
+//! unmatched jumps become traps raised only when they are taken. A `mOO` that
+//! [`Program::analyze`] proves always runs the same command, or always fails,
+//! is lowered to that command or trap instead of a dispatch.
+
+pub mod c;
+pub mod rust;
+pub mod wasm;
+
+use crate::{Command, Program, analysis::Dispatch, jump};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Op {
+    Add(u32),
+    Sub(u32),
+    Right(u32),
+    Left(u32),
+    Clear,
+    Register,
+    Moo,
+    OutputInt,
+    InputInt,
+}
+
+impl Op {
+    /// The operation performed by a command that does not affect control flow.
+    fn of(command: Command) -> Option<Self> {
+        Some(match command {
+            Command::mOo => Self::Left(1),
+            Command::moO => Self::Right(1),
+            Command::Moo => Self::Moo,
+            Command::MOo => Self::Sub(1),
+            Command::MoO => Self::Add(1),
+            Command::OOO => Self::Clear,
+            Command::MMM => Self::Register,
+            Command::OOM => Self::OutputInt,
+            Command::oom => Self::InputInt,
+            Command::moo | Command::mOO | Command::MOO => return None,
+        })
+    }
+
+    fn merge(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (Self::Add(a), Self::Add(b)) => Self::Add(a.checked_add(b)?),
+            (Self::Sub(a), Self::Sub(b)) => Self::Sub(a.checked_add(b)?),
+            (Self::Right(a), Self::Right(b)) => Self::Right(a.checked_add(b)?),
+            (Self::Left(a), Self::Left(b)) => Self::Left(a.checked_add(b)?),
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Trap {
+    BeginlessJumpBackward,
+    EndlessJumpForward,
+    InvalidCommand,
+}
+
+impl Trap {
+    pub(crate) fn message(self) -> &'static str {
+        match self {
+            Self::BeginlessJumpBackward => "beginless jump backward",
+            Self::EndlessJumpForward => "endless jump forward",
+            Self::InvalidCommand => "invalid command",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Target {
+    Block(usize),
+    End,
+    Trap(Trap),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Exit {
+    /// Continue with the next block, or end the program after the last one.
+    Next,
+    /// A `MOO`: continue with the next block unless the current value is 0.
+    Branch { zero: Target },
+    /// A `moo`: jump back to its `MOO`, which is evaluated again.
+    Jump(Target),
+    /// A `mOO`: dispatch on the current value. Since the value is the command
+    /// number, an evaluated `MOO` never jumps and an evaluated `moo` always
+    /// does, to `moo`.
+    Eval { moo: Target },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Block {
+    pub ops: Vec<Op>,
+    pub exit: Exit,
+}
+
+pub(crate) fn lower(program: &[Command]) -> Vec<Block> {
+    let analysis = Program::new(program).analyze();
+    let resolved = program
+        .iter()
+        .enumerate()
+        .map(|(idx, &command)| match command {
+            Command::mOO => resolve(analysis.point(idx)?.dispatch()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut starts = BTreeSet::from([0]);
+
+    for (idx, &command) in program.iter().enumerate() {
+        match command {
+            Command::MOO => {
+                starts.insert(idx);
+                starts.insert(idx + 1);
+                if let Some(moo) = jump::forward(program, idx) {
+                    starts.insert(moo + 1);
+                }
+            }
+            Command::mOO => {
+                // a command that doesn't jump can run in line
+                let inline = matches!(resolved[idx], Some(Ok(command)) if command != Command::moo);
+                if !inline {
+                    starts.insert(idx + 1);
+                }
+            }
+            Command::moo => {
+                starts.insert(idx + 1);
+            }
+            _ => {}
+        }
+    }
+
+    starts.retain(|&idx| idx < program.len());
+
+    let starts = starts.into_iter().collect::<Vec<_>>();
+    let block_of = |idx: usize| match starts.binary_search(&idx) {
+        Ok(block) => Target::Block(block),
+        Err(_) => {
+            debug_assert!(idx >= program.len(), "jump targets start blocks");
+            Target::End
+        }
+    };
+
+    let mut blocks = Vec::with_capacity(starts.len());
+
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(program.len());
+        let mut ops = Vec::<Op>::new();
+        let mut exit = Exit::Next;
+
+        for idx in start..end {
+            let command = match program[idx] {
+                Command::mOO => match resolved[idx] {
+                    // the value is 7, so the evaluated `MOO` never jumps
+                    Some(Ok(Command::MOO)) => continue,
+                    Some(Ok(Command::moo)) => {
+                        exit = Exit::Jump(backward_target(program, idx, &block_of));
+                        continue;
+                    }
+                    Some(Ok(command)) => command,
+                    Some(Err(target)) => {
+                        exit = Exit::Jump(target);
+                        continue;
+                    }
+                    None => Command::mOO,
+                },
+                command => command,
+            };
+
+            if let Some(op) = Op::of(command) {
+                match ops.last().and_then(|last| last.merge(op)) {
+                    Some(merged) => *ops.last_mut().unwrap() = merged,
+                    None => ops.push(op),
+                }
+                continue;
+            }
+
+            exit = match command {
+                Command::MOO => Exit::Branch {
+                    zero: jump::forward(program, idx)
+                        .map(|moo| block_of(moo + 1))
+                        .unwrap_or(Target::Trap(Trap::EndlessJumpForward)),
+                },
+                Command::moo => Exit::Jump(backward_target(program, idx, &block_of)),
+                Command::mOO => Exit::Eval {
+                    moo: backward_target(program, idx, &block_of),
+                },
+                _ => unreachable!(),
+            };
+        }
+
+        blocks.push(Block { ops, exit });
+    }
+
+    blocks
+}
+
+/// What a `mOO` does, if it always runs the same command or always leaves for
+/// the same target, ending the program when it evaluates itself.
+fn resolve(dispatch: Dispatch) -> Option<Result<Command, Target>> {
+    if let Some(command) = dispatch.only() {
+        return Some(Ok(command));
+    }
+    if !dispatch.commands.is_empty() {
+        return None;
+    }
+
+    match (dispatch.may_recurse, dispatch.may_be_invalid) {
+        (true, false) => Some(Err(Target::End)),
+        (false, true) => Some(Err(Target::Trap(Trap::InvalidCommand))),
+        _ => None,
+    }
+}
+
+fn backward_target(program: &[Command], idx: usize, block_of: &impl Fn(usize) -> Target) -> Target {
+    jump::backward(program, idx)
+        .map(block_of)
+        .unwrap_or(Target::Trap(Trap::BeginlessJumpBackward))
+}
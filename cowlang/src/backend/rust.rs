@@ -0,0 +1,164 @@
+//! Compiles a program to Rust source.
+//!
+//! The output is a single `pub fn run` taking the library's [`Input`] and
+//! [`Output`] traits, meant to be pulled into a module with `include!`, for
+//! instance from a file generated by a build script:
+//!
+//! ```ignore
+//! mod hello {
+//!     include!(concat!(env!("OUT_DIR"), "/hello.rs"));
+//! }
+//!
+//! hello::run(&mut input, &mut output)?;
+//! ```
+//!
+//! Input that is not ready fails with [`Error::Io`](crate::Error::Io) rather
+//! than suspending, since the compiled code cannot be resumed.
+//!
+//! [`Input`]: crate::Input
+//! [`Output`]: crate::Output
+
+use super::{Exit, Op, Target, Trap};
+use crate::Command;
+use std::fmt::Write;
+
+pub fn compile(program: &[Command]) -> String {
+    let blocks = super::lower(program);
+    let mut out = String::new();
+
+    out.push_str("// Generated by cowlang. Do not edit.\n\n");
+    out.push_str("#[allow(unused, clippy::all)]\n");
+    out.push_str("pub fn run(\n");
+    out.push_str("    input: &mut dyn ::cowlang::Input,\n");
+    out.push_str("    output: &mut dyn ::cowlang::Output,\n");
+    out.push_str(") -> ::core::result::Result<(), ::cowlang::Error> {\n");
+    out.push_str("    let mut memory: ::std::vec::Vec<u32> = ::std::vec![0];\n");
+    out.push_str("    let mut memory_idx: usize = 0;\n");
+    out.push_str("    let mut register: ::core::option::Option<u32> = None;\n");
+    out.push_str("    let mut block: usize = 0;\n\n");
+    out.push_str("    loop {\n");
+    out.push_str("        match block {\n");
+
+    for (id, block) in blocks.iter().enumerate() {
+        let next = if id + 1 < blocks.len() {
+            Target::Block(id + 1)
+        } else {
+            Target::End
+        };
+
+        writeln!(out, "            {id} => {{").unwrap();
+
+        for &op in &block.ops {
+            write_op(&mut out, op, 4);
+        }
+
+        match block.exit {
+            Exit::Next => {
+                writeln!(out, "                {}", goto(next)).unwrap();
+            }
+            Exit::Branch { zero } => {
+                writeln!(out, "                if memory[memory_idx] == 0 {{").unwrap();
+                writeln!(out, "                    {}", goto(zero)).unwrap();
+                writeln!(out, "                }} else {{").unwrap();
+                writeln!(out, "                    {}", goto(next)).unwrap();
+                writeln!(out, "                }}").unwrap();
+            }
+            Exit::Jump(target) => {
+                writeln!(out, "                {}", goto(target)).unwrap();
+            }
+            Exit::Eval { moo } => {
+                writeln!(out, "                match memory[memory_idx] {{").unwrap();
+
+                for value in 0..12 {
+                    let command = Command::try_from(value).unwrap();
+                    writeln!(out, "                    {value} => {{").unwrap();
+
+                    match command {
+                        Command::moo => {
+                            writeln!(out, "                        {}", goto(moo)).unwrap();
+                        }
+                        Command::mOO => {
+                            writeln!(out, "                        {}", goto(Target::End)).unwrap();
+                        }
+                        Command::MOO => {
+                            writeln!(out, "                        {}", goto(next)).unwrap();
+                        }
+                        command => {
+                            write_op(&mut out, Op::of(command).unwrap(), 6);
+                            writeln!(out, "                        {}", goto(next)).unwrap();
+                        }
+                    }
+
+                    writeln!(out, "                    }}").unwrap();
+                }
+
+                let trap = goto(Target::Trap(Trap::InvalidCommand));
+                writeln!(out, "                    _ => {{").unwrap();
+                writeln!(out, "                        {trap}").unwrap();
+                writeln!(out, "                    }}").unwrap();
+                writeln!(out, "                }}").unwrap();
+            }
+        }
+
+        writeln!(out, "            }}").unwrap();
+    }
+
+    out.push_str("            _ => return Ok(()),\n");
+    out.push_str("        }\n");
+    out.push_str("    }\n");
+    out.push_str("}\n");
+    out
+}
+
+fn write_op(out: &mut String, op: Op, depth: usize) {
+    let indent = "    ".repeat(depth);
+    let value = "memory[memory_idx]";
+
+    match op {
+        Op::Add(n) => writeln!(out, "{indent}{value} = {value}.saturating_add({n});"),
+        Op::Sub(n) => writeln!(out, "{indent}{value} = {value}.saturating_sub({n});"),
+        Op::Right(n) => {
+            writeln!(out, "{indent}memory_idx += {n};").unwrap();
+            writeln!(out, "{indent}if memory_idx >= memory.len() {{").unwrap();
+            writeln!(out, "{indent}    memory.resize(memory_idx + 1, 0);").unwrap();
+            writeln!(out, "{indent}}}")
+        }
+        Op::Left(n) => writeln!(out, "{indent}memory_idx = memory_idx.saturating_sub({n});"),
+        Op::Clear => writeln!(out, "{indent}{value} = 0;"),
+        Op::Register => {
+            writeln!(out, "{indent}match register.take() {{").unwrap();
+            writeln!(out, "{indent}    Some(register_value) => {value} = register_value,").unwrap();
+            writeln!(out, "{indent}    None => register = Some({value}),").unwrap();
+            writeln!(out, "{indent}}}")
+        }
+        Op::Moo => {
+            writeln!(out, "{indent}if {value} == 0 {{").unwrap();
+            writeln!(out, "{indent}    {value} = input.input_char()? as u32;").unwrap();
+            writeln!(out, "{indent}}} else {{").unwrap();
+            writeln!(
+                out,
+                "{indent}    output.output_char(::core::char::from_u32({value}).ok_or(::cowlang::Error::UnwritableChar)?)?;"
+            )
+            .unwrap();
+            writeln!(out, "{indent}}}")
+        }
+        Op::OutputInt => writeln!(out, "{indent}output.output_int({value})?;"),
+        Op::InputInt => writeln!(out, "{indent}{value} = input.input_int()?;"),
+    }
+    .unwrap();
+}
+
+fn goto(target: Target) -> String {
+    match target {
+        Target::Block(id) => format!("block = {id};"),
+        Target::End => "return Ok(());".to_string(),
+        Target::Trap(trap) => {
+            let variant = match trap {
+                Trap::BeginlessJumpBackward => "BeginlessJumpBackward",
+                Trap::EndlessJumpForward => "EndlessJumpForward",
+                Trap::InvalidCommand => "InvalidCommand",
+            };
+            format!("return Err(::cowlang::Error::{variant});")
+        }
+    }
+}
@@ -0,0 +1,549 @@
+//! Compiles a program to a WebAssembly module.
+//!
+//! The module imports its I/O from `env`:
+//!
+//! - `input_char() -> i32` and `input_int() -> i32` read a value;
+//! - `output_char(i32)` and `output_int(i32)` write one.
+//!
+//! Values are `u32`s passed as `i32`s, and the host is expected to trap when
+//! I/O fails. The module exports its `memory`, holding one cell per 4 bytes,
+//! and a `run() -> i32` function returning 0 when the program completes or an
+//! error code that [`error`] turns back into an [`Error`].
+
+use super::{Exit, Op, Target, Trap};
+use crate::{Command, Error};
+
+const SECTION_TYPE: u8 = 1;
+const SECTION_IMPORT: u8 = 2;
+const SECTION_FUNCTION: u8 = 3;
+const SECTION_MEMORY: u8 = 5;
+const SECTION_GLOBAL: u8 = 6;
+const SECTION_EXPORT: u8 = 7;
+const SECTION_CODE: u8 = 10;
+
+const FUNC_TYPE: u8 = 0x60;
+const EXTERN_FUNC: u8 = 0x00;
+const EXTERN_MEMORY: u8 = 0x02;
+const LIMITS_MIN: u8 = 0x00;
+const MUTABLE: u8 = 0x01;
+const BLOCK_TYPE_EMPTY: u8 = 0x40;
+const I32: u8 = 0x7F;
+
+const UNREACHABLE: u8 = 0x00;
+const BLOCK: u8 = 0x02;
+const LOOP: u8 = 0x03;
+const IF: u8 = 0x04;
+const ELSE: u8 = 0x05;
+const END: u8 = 0x0B;
+const BR: u8 = 0x0C;
+const BR_IF: u8 = 0x0D;
+const BR_TABLE: u8 = 0x0E;
+const RETURN: u8 = 0x0F;
+const CALL: u8 = 0x10;
+const SELECT: u8 = 0x1B;
+const LOCAL_GET: u8 = 0x20;
+const LOCAL_SET: u8 = 0x21;
+const LOCAL_TEE: u8 = 0x22;
+const GLOBAL_GET: u8 = 0x23;
+const GLOBAL_SET: u8 = 0x24;
+const I32_LOAD: u8 = 0x28;
+const I32_STORE: u8 = 0x36;
+const MEMORY_SIZE: u8 = 0x3F;
+const MEMORY_GROW: u8 = 0x40;
+const I32_CONST: u8 = 0x41;
+const I32_EQZ: u8 = 0x45;
+const I32_EQ: u8 = 0x46;
+const I32_LT_U: u8 = 0x49;
+const I32_GT_U: u8 = 0x4B;
+const I32_LE_U: u8 = 0x4D;
+const I32_ADD: u8 = 0x6A;
+const I32_SUB: u8 = 0x6B;
+const I32_OR: u8 = 0x72;
+const I32_SHL: u8 = 0x74;
+const I32_SHR_U: u8 = 0x76;
+
+const INPUT_CHAR: u32 = 0;
+const INPUT_INT: u32 = 1;
+const OUTPUT_CHAR: u32 = 2;
+const OUTPUT_INT: u32 = 3;
+const ADD: u32 = 4;
+const SUB: u32 = 5;
+const RIGHT: u32 = 6;
+const LEFT: u32 = 7;
+const REGISTER: u32 = 8;
+const MOO: u32 = 9;
+const RUN: u32 = 10;
+
+const MEMORY_IDX: u32 = 0;
+const REGISTER_VALUE: u32 = 1;
+const REGISTER_FULL: u32 = 2;
+
+const UNWRITABLE_CHAR: i32 = 5;
+
+fn trap_code(trap: Trap) -> i32 {
+    match trap {
+        Trap::BeginlessJumpBackward => 1,
+        Trap::EndlessJumpForward => 2,
+        Trap::InvalidCommand => 3,
+    }
+}
+
+/// Converts a code returned by a compiled module's `run` to the error it
+/// stands for, or `None` when the program completed.
+pub fn error(code: i32) -> Option<Error> {
+    Some(match code {
+        1 => Error::BeginlessJumpBackward,
+        2 => Error::EndlessJumpForward,
+        3 => Error::InvalidCommand,
+        UNWRITABLE_CHAR => Error::UnwritableChar,
+        _ => return None,
+    })
+}
+
+pub fn compile(program: &[Command]) -> Vec<u8> {
+    let mut module = b"\0asm".to_vec();
+    module.extend(1u32.to_le_bytes());
+
+    // () -> i32, (i32) -> (), () -> ()
+    section(&mut module, SECTION_TYPE, |s| {
+        uleb(s, 3);
+        s.extend([FUNC_TYPE, 0, 1, I32]);
+        s.extend([FUNC_TYPE, 1, I32, 0]);
+        s.extend([FUNC_TYPE, 0, 0]);
+    });
+
+    section(&mut module, SECTION_IMPORT, |s| {
+        uleb(s, 4);
+        for (name, ty) in [
+            ("input_char", 0),
+            ("input_int", 0),
+            ("output_char", 1),
+            ("output_int", 1),
+        ] {
+            bytes(s, b"env");
+            bytes(s, name.as_bytes());
+            s.push(EXTERN_FUNC);
+            uleb(s, ty);
+        }
+    });
+
+    // add, sub, right, left, register, moo, run
+    section(&mut module, SECTION_FUNCTION, |s| {
+        uleb(s, 7);
+        s.extend([1, 1, 1, 1, 2, 0, 0]);
+    });
+
+    // one memory of at least a page, with no maximum
+    section(&mut module, SECTION_MEMORY, |s| {
+        uleb(s, 1);
+        s.extend([LIMITS_MIN, 1]);
+    });
+
+    // memory_idx, register_value, register_full, all starting at 0
+    section(&mut module, SECTION_GLOBAL, |s| {
+        uleb(s, 3);
+        for _ in 0..3 {
+            s.extend([I32, MUTABLE, I32_CONST, 0, END]);
+        }
+    });
+
+    section(&mut module, SECTION_EXPORT, |s| {
+        uleb(s, 2);
+        bytes(s, b"run");
+        s.push(EXTERN_FUNC);
+        uleb(s, RUN);
+        bytes(s, b"memory");
+        s.push(EXTERN_MEMORY);
+        uleb(s, 0);
+    });
+
+    section(&mut module, SECTION_CODE, |s| {
+        uleb(s, 7);
+        function(s, 3, add);
+        function(s, 2, sub);
+        function(s, 0, right);
+        function(s, 0, left);
+        function(s, 1, register);
+        function(s, 2, moo);
+        function(s, 2, |code| run(code, program));
+    });
+
+    module
+}
+
+fn add(code: &mut Code) {
+    code.address().local_tee(1);
+    code.i32_const(-1);
+    code.local_get(1)
+        .load()
+        .local_tee(2)
+        .local_get(0)
+        .op(I32_ADD)
+        .local_tee(3);
+    code.local_get(3).local_get(2).op(I32_LT_U);
+    code.op(SELECT).store();
+}
+
+fn sub(code: &mut Code) {
+    code.address().local_tee(1);
+    code.i32_const(0);
+    code.local_get(1)
+        .load()
+        .local_tee(2)
+        .local_get(0)
+        .op(I32_SUB);
+    code.local_get(2).local_get(0).op(I32_LT_U);
+    code.op(SELECT).store();
+}
+
+fn right(code: &mut Code) {
+    code.global_get(MEMORY_IDX)
+        .local_get(0)
+        .op(I32_ADD)
+        .global_set(MEMORY_IDX);
+
+    // grow the memory when the cell at `memory_idx` lies past its end
+    code.block();
+    code.global_get(MEMORY_IDX)
+        .i32_const(1)
+        .op(I32_ADD)
+        .i32_const(2)
+        .op(I32_SHL);
+    code.memory_size().i32_const(16).op(I32_SHL);
+    code.op(I32_LE_U).br_if(0);
+    code.global_get(MEMORY_IDX)
+        .i32_const(1)
+        .op(I32_ADD)
+        .i32_const(2)
+        .op(I32_SHL);
+    code.i32_const(0xFFFF)
+        .op(I32_ADD)
+        .i32_const(16)
+        .op(I32_SHR_U);
+    code.memory_size().op(I32_SUB);
+    code.memory_grow().i32_const(-1).op(I32_EQ);
+    code.if_().op(UNREACHABLE).end();
+    code.end();
+}
+
+fn left(code: &mut Code) {
+    code.i32_const(0);
+    code.global_get(MEMORY_IDX).local_get(0).op(I32_SUB);
+    code.global_get(MEMORY_IDX).local_get(0).op(I32_LT_U);
+    code.op(SELECT).global_set(MEMORY_IDX);
+}
+
+fn register(code: &mut Code) {
+    code.address().local_set(0);
+    code.global_get(REGISTER_FULL).if_();
+    code.local_get(0).global_get(REGISTER_VALUE).store();
+    code.i32_const(0).global_set(REGISTER_FULL);
+    code.op(ELSE);
+    code.local_get(0).load().global_set(REGISTER_VALUE);
+    code.i32_const(1).global_set(REGISTER_FULL);
+    code.end();
+}
+
+fn moo(code: &mut Code) {
+    code.address().local_tee(0).load().local_tee(1).op(I32_EQZ);
+    code.if_();
+    code.local_get(0).call(INPUT_CHAR).store();
+    code.i32_const(0).op(RETURN);
+    code.end();
+
+    // values above char::MAX or in the surrogate range are unwritable
+    code.local_get(1).i32_const(0x10FFFF).op(I32_GT_U);
+    code.local_get(1)
+        .i32_const(0xD800)
+        .op(I32_SUB)
+        .i32_const(0x800)
+        .op(I32_LT_U);
+    code.op(I32_OR).if_();
+    code.i32_const(UNWRITABLE_CHAR).op(RETURN);
+    code.end();
+
+    code.local_get(1).call(OUTPUT_CHAR);
+    code.i32_const(0);
+}
+
+/// Emits the blocks as the arms of a `br_table` inside a loop: each arm falls
+/// through into the next, and jumps set the `block` local (local 0) before
+/// branching back to the top of the loop.
+fn run(code: &mut Code, program: &[Command]) {
+    let blocks = super::lower(program);
+    let n = blocks.len() as u32;
+
+    if n > 0 {
+        code.block();
+        code.loop_();
+        for _ in 0..n {
+            code.block();
+        }
+
+        code.local_get(0);
+        code.op(BR_TABLE);
+        uleb(&mut code.0, n);
+        for id in 0..n {
+            uleb(&mut code.0, id);
+        }
+        uleb(&mut code.0, n + 1);
+
+        for (id, block) in blocks.iter().enumerate() {
+            code.end();
+
+            let loop_depth = n - 1 - id as u32;
+
+            for &op in &block.ops {
+                run_op(code, op);
+            }
+
+            match block.exit {
+                Exit::Next => {}
+                Exit::Branch { zero } => {
+                    code.address().load().op(I32_EQZ).if_();
+                    goto(code, zero, loop_depth + 1);
+                    code.end();
+                }
+                Exit::Jump(target) => goto(code, target, loop_depth),
+                Exit::Eval { moo } => {
+                    code.block();
+                    code.block();
+                    for _ in 0..12 {
+                        code.block();
+                    }
+
+                    code.address().load().op(BR_TABLE);
+                    uleb(&mut code.0, 12);
+                    for value in 0..12 {
+                        uleb(&mut code.0, value);
+                    }
+                    uleb(&mut code.0, 12);
+
+                    for value in 0..12 {
+                        code.end();
+
+                        let done_depth = 12 - value;
+
+                        match Command::try_from(value).unwrap() {
+                            Command::moo => goto(code, moo, done_depth + 1 + loop_depth),
+                            Command::mOO => goto(code, Target::End, 0),
+                            Command::MOO => {}
+                            command => run_op(code, Op::of(command).unwrap()),
+                        }
+
+                        code.br(done_depth);
+                    }
+
+                    code.end();
+                    goto(code, Target::Trap(Trap::InvalidCommand), 0);
+                    code.end();
+                }
+            }
+        }
+
+        code.end();
+        code.end();
+    }
+
+    code.i32_const(0);
+}
+
+fn run_op(code: &mut Code, op: Op) {
+    match op {
+        Op::Add(n) => code.i32_const(n as i32).call(ADD),
+        Op::Sub(n) => code.i32_const(n as i32).call(SUB),
+        Op::Right(n) => code.i32_const(n as i32).call(RIGHT),
+        Op::Left(n) => code.i32_const(n as i32).call(LEFT),
+        Op::Clear => code.address().i32_const(0).store(),
+        Op::Register => code.call(REGISTER),
+        Op::Moo => {
+            code.call(MOO).local_tee(1).if_();
+            code.local_get(1).op(RETURN);
+            code.end()
+        }
+        Op::OutputInt => code.address().load().call(OUTPUT_INT),
+        Op::InputInt => code.address().call(INPUT_INT).store(),
+    };
+}
+
+fn goto(code: &mut Code, target: Target, loop_depth: u32) {
+    match target {
+        Target::Block(id) => {
+            code.i32_const(id as i32).local_set(0).br(loop_depth);
+        }
+        Target::End => {
+            code.i32_const(0).op(RETURN);
+        }
+        Target::Trap(trap) => {
+            code.i32_const(trap_code(trap)).op(RETURN);
+        }
+    }
+}
+
+struct Code(Vec<u8>);
+
+impl Code {
+    fn op(&mut self, op: u8) -> &mut Self {
+        self.0.push(op);
+        self
+    }
+
+    fn block(&mut self) -> &mut Self {
+        self.0.extend([BLOCK, BLOCK_TYPE_EMPTY]);
+        self
+    }
+
+    fn loop_(&mut self) -> &mut Self {
+        self.0.extend([LOOP, BLOCK_TYPE_EMPTY]);
+        self
+    }
+
+    fn if_(&mut self) -> &mut Self {
+        self.0.extend([IF, BLOCK_TYPE_EMPTY]);
+        self
+    }
+
+    fn end(&mut self) -> &mut Self {
+        self.op(END)
+    }
+
+    fn br(&mut self, depth: u32) -> &mut Self {
+        self.0.push(BR);
+        uleb(&mut self.0, depth);
+        self
+    }
+
+    fn br_if(&mut self, depth: u32) -> &mut Self {
+        self.0.push(BR_IF);
+        uleb(&mut self.0, depth);
+        self
+    }
+
+    fn call(&mut self, func: u32) -> &mut Self {
+        self.0.push(CALL);
+        uleb(&mut self.0, func);
+        self
+    }
+
+    fn local_get(&mut self, local: u32) -> &mut Self {
+        self.0.push(LOCAL_GET);
+        uleb(&mut self.0, local);
+        self
+    }
+
+    fn local_set(&mut self, local: u32) -> &mut Self {
+        self.0.push(LOCAL_SET);
+        uleb(&mut self.0, local);
+        self
+    }
+
+    fn local_tee(&mut self, local: u32) -> &mut Self {
+        self.0.push(LOCAL_TEE);
+        uleb(&mut self.0, local);
+        self
+    }
+
+    fn global_get(&mut self, global: u32) -> &mut Self {
+        self.0.push(GLOBAL_GET);
+        uleb(&mut self.0, global);
+        self
+    }
+
+    fn global_set(&mut self, global: u32) -> &mut Self {
+        self.0.push(GLOBAL_SET);
+        uleb(&mut self.0, global);
+        self
+    }
+
+    fn i32_const(&mut self, value: i32) -> &mut Self {
+        self.0.push(I32_CONST);
+        sleb(&mut self.0, value);
+        self
+    }
+
+    fn memory_size(&mut self) -> &mut Self {
+        self.0.extend([MEMORY_SIZE, 0]);
+        self
+    }
+
+    fn memory_grow(&mut self) -> &mut Self {
+        self.0.extend([MEMORY_GROW, 0]);
+        self
+    }
+
+    /// Loads the cell at the address on the stack, with 4-byte alignment.
+    fn load(&mut self) -> &mut Self {
+        self.0.extend([I32_LOAD, 2, 0]);
+        self
+    }
+
+    /// Stores into the cell at the address on the stack, with 4-byte
+    /// alignment.
+    fn store(&mut self) -> &mut Self {
+        self.0.extend([I32_STORE, 2, 0]);
+        self
+    }
+
+    /// Pushes the byte address of the current cell.
+    fn address(&mut self) -> &mut Self {
+        self.global_get(MEMORY_IDX).i32_const(2).op(I32_SHL)
+    }
+}
+
+fn function(out: &mut Vec<u8>, locals: u32, body: impl FnOnce(&mut Code)) {
+    let mut code = Code(Vec::new());
+
+    if locals > 0 {
+        uleb(&mut code.0, 1);
+        uleb(&mut code.0, locals);
+        code.0.push(I32);
+    } else {
+        uleb(&mut code.0, 0);
+    }
+
+    body(&mut code);
+    code.end();
+
+    uleb(out, code.0.len() as u32);
+    out.extend(code.0);
+}
+
+fn section(out: &mut Vec<u8>, id: u8, contents: impl FnOnce(&mut Vec<u8>)) {
+    let mut section = Vec::new();
+    contents(&mut section);
+
+    out.push(id);
+    uleb(out, section.len() as u32);
+    out.extend(section);
+}
+
+fn bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    uleb(out, bytes.len() as u32);
+    out.extend(bytes);
+}
+
+fn uleb(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn sleb(out: &mut Vec<u8>, mut value: i32) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{Cowlang, Dialect, Error, Program, Reader, Status, Writer};
+use std::{
+    io::Cursor,
+    num::NonZeroUsize,
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone)]
+pub struct Case {
+    pub name: String,
+    pub input: String,
+    pub expected: String,
+    /// Stops the run after this many steps and compares the output produced so
+    /// far, for programs that are not meant to terminate.
+    pub max_steps: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Job<'a> {
+    pub name: String,
+    pub program: Program<'a>,
+    pub case: Case,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Options {
+    pub max_steps: u64,
+    pub max_memory: usize,
+    pub threads: NonZeroUsize,
+    pub dialect: Dialect,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            max_steps: 10_000_000,
+            max_memory: 1 << 20,
+            threads: thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+            dialect: Dialect::REFERENCE,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Outcome {
+    pub name: String,
+    pub case: String,
+    pub verdict: Verdict,
+    pub output: String,
+    pub steps: u64,
+    pub duration: Duration,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        matches!(self.verdict, Verdict::Pass)
+    }
+}
+
+#[derive(Debug)]
+pub enum Verdict {
+    Pass,
+    Fail { diff: String },
+    Error(Error),
+    StepLimit,
+    MemoryLimit,
+}
+
+impl Verdict {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Pass => "pass",
+            Self::Fail { .. } => "fail",
+            Self::Error(_) => "error",
+            Self::StepLimit => "step-limit",
+            Self::MemoryLimit => "memory-limit",
+        }
+    }
+}
+
+/// Runs every job on a pool of `options.threads` threads, returning the
+/// outcomes in the same order as `jobs`.
+pub fn run(jobs: &[Job], options: &Options) -> Vec<Outcome> {
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(jobs.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..options.threads.get().min(jobs.len()) {
+            scope.spawn(|| {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(idx) else {
+                        break;
+                    };
+
+                    let outcome = run_job(job, options);
+                    outcomes.lock().unwrap().push((idx, outcome));
+                }
+            });
+        }
+    });
+
+    let mut outcomes = outcomes.into_inner().unwrap();
+    outcomes.sort_by_key(|(idx, _)| *idx);
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+pub fn run_job(job: &Job, options: &Options) -> Outcome {
+    let start = Instant::now();
+
+    let mut interp = Cowlang::new(crate::Options {
+        program: Program::new(&*job.program),
+        input: Reader::new(Cursor::new(job.case.input.as_bytes())),
+        output: Writer::new(Vec::new()),
+        dialect: options.dialect,
+    });
+
+    let mut steps = 0;
+
+    let result = loop {
+        if let Some(max_steps) = job.case.max_steps
+            && steps >= max_steps
+        {
+            break None;
+        }
+        if steps >= options.max_steps {
+            break Some(Verdict::StepLimit);
+        }
+
+        let status = interp.advance();
+        if interp.memory().len() > options.max_memory {
+            break Some(Verdict::MemoryLimit);
+        }
+
+        match status {
+            Ok(Status::Completed) => break None,
+            Ok(Status::Running) => {}
+            Ok(Status::NeedsInput(_)) => unreachable!("in-memory input never blocks"),
+            Err(error) => break Some(Verdict::Error(error)),
+        }
+
+        steps += 1;
+    };
+
+    let duration = start.elapsed();
+    let (_, output) = interp.into_io();
+    let output = String::from_utf8(output.into_inner()).expect("output is written from chars");
+
+    let verdict = result.unwrap_or_else(|| {
+        if output == job.case.expected {
+            Verdict::Pass
+        } else {
+            Verdict::Fail {
+                diff: diff(&job.case.expected, &output),
+            }
+        }
+    });
+
+    Outcome {
+        name: job.name.clone(),
+        case: job.case.name.clone(),
+        verdict,
+        output,
+        steps,
+        duration,
+    }
+}
+
+/// The most cells [`diff`] builds its table from, after the lines the outputs
+/// start and end with are set aside. Beyond this, every line in between is
+/// shown as removed and then added.
+pub const MAX_DIFF_CELLS: usize = 1 << 20;
+
+/// Produces a line diff between the expected and actual output, with removed
+/// lines prefixed by `-`, added lines by `+` and common lines by a space.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected = expected.split_inclusive('\n').collect::<Vec<_>>();
+    let actual = actual.split_inclusive('\n').collect::<Vec<_>>();
+
+    let prefix = expected
+        .iter()
+        .zip(&actual)
+        .take_while(|(expected, actual)| expected == actual)
+        .count();
+    let suffix = expected[prefix..]
+        .iter()
+        .rev()
+        .zip(actual[prefix..].iter().rev())
+        .take_while(|(expected, actual)| expected == actual)
+        .count();
+
+    let mut out = String::new();
+    let mut push = |prefix: char, line: &str| {
+        out.push(prefix);
+        out.push_str(line);
+        if !line.ends_with('\n') {
+            out.push_str("\n\\ No newline at end of output\n");
+        }
+    };
+
+    for line in &expected[..prefix] {
+        push(' ', line);
+    }
+
+    let removed = &expected[prefix..expected.len() - suffix];
+    let added = &actual[prefix..actual.len() - suffix];
+    let cells = (removed.len() + 1).saturating_mul(added.len() + 1);
+
+    if cells > MAX_DIFF_CELLS {
+        for line in removed {
+            push('-', line);
+        }
+        for line in added {
+            push('+', line);
+        }
+    } else {
+        let mut lcs = vec![vec![0usize; added.len() + 1]; removed.len() + 1];
+
+        for i in (0..removed.len()).rev() {
+            for j in (0..added.len()).rev() {
+                lcs[i][j] = if removed[i] == added[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+
+        while i < removed.len() || j < added.len() {
+            if i < removed.len() && j < added.len() && removed[i] == added[j] {
+                push(' ', removed[i]);
+                i += 1;
+                j += 1;
+            } else if i < removed.len() && (j == added.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                push('-', removed[i]);
+                i += 1;
+            } else {
+                push('+', added[j]);
+                j += 1;
+            }
+        }
+    }
+
+    for line in &expected[expected.len() - suffix..] {
+        push(' ', line);
+    }
+
+    out
+}
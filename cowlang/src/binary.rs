@@ -0,0 +1,125 @@
+//! A compact binary encoding of programs.
+//!
+//! An encoded program starts with the [`MAGIC`] bytes, a version byte and the
+//! number of commands as a little-endian `u64`. The commands follow as their
+//! numbers, two to a byte with the first in the high half. If the count is
+//! odd, the last low half is 0.
+
+use crate::{Command, Program};
+use std::{fmt, io, path::Path};
+
+pub const MAGIC: [u8; 4] = *b"COW\0";
+pub const VERSION: u8 = 1;
+
+const HEADER_LEN: usize = MAGIC.len() + 1 + 8;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The data doesn't start with [`MAGIC`].
+    NotEncoded,
+    UnsupportedVersion(u8),
+    /// The data ends before the number of commands in the header.
+    Truncated,
+    TrailingData,
+    InvalidCommand {
+        idx: usize,
+        value: u8,
+    },
+    Io(io::Error),
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEncoded => f.write_str("not an encoded program"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported version {version}"),
+            Self::Truncated => f.write_str("program is truncated"),
+            Self::TrailingData => f.write_str("unexpected data after the program"),
+            Self::InvalidCommand { idx, value } => {
+                write!(f, "invalid command {value} at index {idx}")
+            }
+            Self::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Whether `bytes` looks like an encoded program, as opposed to source.
+pub fn is_encoded(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+pub fn encode(program: &[Command]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + program.len().div_ceil(2));
+    bytes.extend(MAGIC);
+    bytes.push(VERSION);
+    bytes.extend((program.len() as u64).to_le_bytes());
+
+    for pair in program.chunks(2) {
+        let low = pair.get(1).map_or(0, |&command| command as u8);
+        bytes.push((pair[0] as u8) << 4 | low);
+    }
+
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Program<'static>, Error> {
+    let Some(rest) = bytes.strip_prefix(&MAGIC) else {
+        return Err(Error::NotEncoded);
+    };
+    let Some((&version, rest)) = rest.split_first() else {
+        return Err(Error::Truncated);
+    };
+    if version != VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    let Some((len, rest)) = rest.split_first_chunk::<8>() else {
+        return Err(Error::Truncated);
+    };
+
+    let len = usize::try_from(u64::from_le_bytes(*len)).map_err(|_| Error::Truncated)?;
+    let expected = len.div_ceil(2);
+    if rest.len() < expected {
+        return Err(Error::Truncated);
+    }
+    if rest.len() > expected {
+        return Err(Error::TrailingData);
+    }
+
+    let mut commands = Vec::with_capacity(len);
+    for (idx, byte) in rest.iter().enumerate() {
+        for (half, value) in [(0, byte >> 4), (1, byte & 0xf)] {
+            let idx = idx * 2 + half;
+
+            if idx == len {
+                if value != 0 {
+                    return Err(Error::TrailingData);
+                }
+                break;
+            }
+
+            let command = Command::try_from(u32::from(value))
+                .map_err(|()| Error::InvalidCommand { idx, value })?;
+            commands.push(command);
+        }
+    }
+
+    Ok(Program::new(commands))
+}
+
+/// Encodes `program` into the file at `path`.
+pub fn save(program: &[Command], path: impl AsRef<Path>) -> io::Result<()> {
+    std::fs::write(path, encode(program))
+}
+
+/// Decodes the program in the file at `path`.
+pub fn load(path: impl AsRef<Path>) -> Result<Program<'static>, Error> {
+    decode(&std::fs::read(path)?)
+}
@@ -0,0 +1,131 @@
+//! Translation between Brainfuck and COW.
+//!
+//! Brainfuck commands map onto COW ones as follows:
+//!
+//! | Brainfuck | COW       |
+//! |-----------|-----------|
+//! | `+`       | `MoO`     |
+//! | `-`       | `MOo`     |
+//! | `<`       | `mOo`     |
+//! | `>`       | `moO`     |
+//! | `[`       | `MOO`     |
+//! | `]`       | `moo`     |
+//! | `.`       | `Moo`     |
+//! | `,`       | `OOO Moo` |
+//!
+//! The two languages differ at the edges: COW cells are `u32`s that saturate
+//! at 0 and `u32::MAX` instead of wrapping, the tape is unbounded to the
+//! right, and `Moo` reads rather than writes when the current value is 0, so
+//! `.` on an empty cell does not output a NUL.
+
+use crate::{Builder, Command, Program, jump};
+use std::fmt;
+
+/// Translates Brainfuck source to a program, ignoring any other characters.
+///
+/// Loops are padded as needed so that COW's jumps match the brackets.
+pub fn to_program(source: &str) -> Program<'static> {
+    let mut builder = Builder::new();
+
+    for char in source.chars() {
+        match char {
+            '+' => builder.push(Command::MoO),
+            '-' => builder.push(Command::MOo),
+            '<' => builder.push(Command::mOo),
+            '>' => builder.push(Command::moO),
+            '[' => builder.push(Command::MOO),
+            ']' => builder.push(Command::moo),
+            '.' => builder.push(Command::Moo),
+            ',' => builder.extend([Command::OOO, Command::Moo]),
+            _ => continue,
+        };
+    }
+
+    builder.build()
+}
+
+/// Translates a program to Brainfuck.
+///
+/// `OOO` becomes `[-]` and `OOO Moo` becomes `,`; any other `Moo` is taken to
+/// be a write. Commands with no Brainfuck equivalent, and loops whose jumps
+/// don't match the way their brackets would, are reported instead.
+pub fn from_program(program: &[Command]) -> Result<String, Untranslatable> {
+    let mut out = String::new();
+    let mut untranslatable = Vec::new();
+    let mut open = Vec::new();
+    let mut idx = 0;
+
+    while idx < program.len() {
+        let command = program[idx];
+
+        match command {
+            Command::MoO => out.push('+'),
+            Command::MOo => out.push('-'),
+            Command::mOo => out.push('<'),
+            Command::moO => out.push('>'),
+            Command::Moo => out.push('.'),
+            Command::OOO if program.get(idx + 1) == Some(&Command::Moo) => {
+                out.push(',');
+                idx += 1;
+            }
+            Command::OOO => out.push_str("[-]"),
+            Command::MOO => {
+                open.push(idx);
+                out.push('[');
+            }
+            Command::moo => {
+                let matches = match open.pop() {
+                    Some(start) => {
+                        jump::forward(program, start) == Some(idx)
+                            && jump::backward(program, idx) == Some(start)
+                    }
+                    None => false,
+                };
+
+                if !matches {
+                    untranslatable.push((idx, command));
+                }
+                out.push(']');
+            }
+            Command::mOO | Command::MMM | Command::OOM | Command::oom => {
+                untranslatable.push((idx, command));
+            }
+        }
+
+        idx += 1;
+    }
+
+    untranslatable.extend(open.into_iter().map(|idx| (idx, Command::MOO)));
+    untranslatable.sort_unstable_by_key(|&(idx, _)| idx);
+
+    if untranslatable.is_empty() {
+        Ok(out)
+    } else {
+        Err(Untranslatable(untranslatable))
+    }
+}
+
+/// The commands that kept a program from being translated, with their
+/// indices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Untranslatable(Vec<(usize, Command)>);
+
+impl Untranslatable {
+    pub fn commands(&self) -> &[(usize, Command)] {
+        &self.0
+    }
+}
+
+impl std::error::Error for Untranslatable {}
+
+impl fmt::Display for Untranslatable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("cannot translate")?;
+
+        for (i, (idx, command)) in self.0.iter().enumerate() {
+            let sep = if i == 0 { " " } else { ", " };
+            write!(f, "{sep}{command} at {idx}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{Command, Program};
+
+/// Builds a program command by command, keeping loops structured.
+///
+/// The interpreter's jumps skip the command next to the one they start from,
+/// so a `MOO` directly followed by `MOO` or `moo`, or a `moo` directly
+/// following another `moo`, would not match the way the nesting reads. The
+/// builder pads those spots with `moO mOo`, which may grow the tape but leaves
+/// the pointer and the value of every existing cell unchanged, so every `MOO`
+/// pushed matches the `moo` that closes it.
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    commands: Vec<Command>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, command: Command) -> &mut Self {
+        let needs_padding = matches!(
+            (self.commands.last(), command),
+            (Some(Command::MOO), Command::MOO | Command::moo) | (Some(Command::moo), Command::moo)
+        );
+
+        if needs_padding {
+            self.commands.extend([Command::moO, Command::mOo]);
+        }
+
+        self.commands.push(command);
+        self
+    }
+
+    pub fn extend(&mut self, commands: impl IntoIterator<Item = Command>) -> &mut Self {
+        for command in commands {
+            self.push(command);
+        }
+        self
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    pub fn build(self) -> Program<'static> {
+        Program::new(self.commands)
+    }
+}
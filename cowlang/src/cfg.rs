@@ -0,0 +1,231 @@
+//! Builds the control-flow graph of a program.
+//!
+//! Every `MOO` is a block of its own, since a `moo` jumps back to it and
+//! evaluates it again rather than continuing after it. Blocks end after each
+//! `MOO`, `moo` and `mOO`, and wherever a jump may land.
+
+use crate::{Command, jump};
+use std::{collections::BTreeSet, fmt::Write};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph {
+    pub blocks: Vec<Block>,
+    pub edges: Vec<Edge>,
+}
+
+/// A run of commands that always execute together, from index `start`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub start: usize,
+    pub commands: Vec<Command>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Edge {
+    /// The index of the block the edge leaves.
+    pub from: usize,
+    pub to: Node,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// The index of a block.
+    Block(usize),
+    /// The end of the program.
+    Exit,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// Continuing to the next command, including past a `MOO` whose value
+    /// isn't 0.
+    Next,
+    /// A `MOO` skipping past its matching `moo` when the value is 0.
+    Forward,
+    /// A `moo` jumping back to its matching `MOO`.
+    Backward,
+    /// A `mOO` evaluating `moo`, which jumps back from the `mOO` itself. An
+    /// evaluated `MOO` sees the value 7, so it never jumps.
+    Eval,
+}
+
+impl EdgeKind {
+    fn name(self) -> &'static str {
+        match self {
+            EdgeKind::Next => "next",
+            EdgeKind::Forward => "forward",
+            EdgeKind::Backward => "backward",
+            EdgeKind::Eval => "eval",
+        }
+    }
+}
+
+pub fn build(program: &[Command]) -> Graph {
+    let mut starts = BTreeSet::new();
+    if !program.is_empty() {
+        starts.insert(0);
+    }
+
+    for (idx, &command) in program.iter().enumerate() {
+        match command {
+            Command::MOO => {
+                starts.insert(idx);
+                starts.insert(idx + 1);
+                starts.extend(jump::forward(program, idx).map(|end| end + 1));
+            }
+            Command::moo => {
+                starts.insert(idx + 1);
+            }
+            Command::mOO => {
+                starts.insert(idx + 1);
+            }
+            _ => {}
+        }
+    }
+
+    let starts = starts
+        .into_iter()
+        .filter(|&start| start < program.len())
+        .collect::<Vec<_>>();
+
+    let node = |idx: usize| match starts.binary_search(&idx) {
+        Ok(block) => Node::Block(block),
+        Err(_) => Node::Exit,
+    };
+
+    let mut blocks = Vec::new();
+    let mut edges = Vec::new();
+
+    for (block, &start) in starts.iter().enumerate() {
+        let end = starts.get(block + 1).copied().unwrap_or(program.len());
+        let last = end - 1;
+        let mut edge = |idx: usize, kind| {
+            let edge = Edge {
+                from: block,
+                to: node(idx),
+                kind,
+            };
+            if !edges.contains(&edge) {
+                edges.push(edge);
+            }
+        };
+
+        match program[last] {
+            Command::MOO => {
+                edge(end, EdgeKind::Next);
+                if let Some(target) = jump::forward(program, last) {
+                    edge(target + 1, EdgeKind::Forward);
+                }
+            }
+            Command::moo => {
+                if let Some(target) = jump::backward(program, last) {
+                    edge(target, EdgeKind::Backward);
+                }
+            }
+            Command::mOO => {
+                edge(end, EdgeKind::Next);
+                if let Some(target) = jump::backward(program, last) {
+                    edge(target, EdgeKind::Eval);
+                }
+            }
+            _ => edge(end, EdgeKind::Next),
+        }
+
+        blocks.push(Block {
+            start,
+            commands: program[start..end].to_vec(),
+        });
+    }
+
+    Graph { blocks, edges }
+}
+
+impl Graph {
+    /// Renders the graph in Graphviz's DOT language.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        out.push_str("digraph cow {\n");
+        out.push_str("    node [shape=box, fontname=monospace];\n");
+        out.push_str("    exit [shape=doublecircle, label=\"exit\"];\n");
+
+        for (idx, block) in self.blocks.iter().enumerate() {
+            let mut label = format!("{}:", block.start);
+            for (i, command) in block.commands.iter().enumerate() {
+                label.push_str(if i > 0 && i % 8 == 0 { "\\l    " } else { " " });
+                write!(label, "{command}").unwrap();
+            }
+            writeln!(out, "    b{idx} [label=\"{label}\\l\"];").unwrap();
+        }
+
+        for edge in &self.edges {
+            let to = match edge.to {
+                Node::Block(idx) => format!("b{idx}"),
+                Node::Exit => "exit".to_string(),
+            };
+            let style = match edge.kind {
+                EdgeKind::Next => "",
+                EdgeKind::Forward => " [label=\"0\", style=dashed]",
+                EdgeKind::Backward => " [label=\"moo\", color=blue]",
+                EdgeKind::Eval => " [label=\"mOO\", style=dotted]",
+            };
+            writeln!(out, "    b{} -> {to}{style};", edge.from).unwrap();
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Renders the graph as JSON, with each edge's `to` either a block index
+    /// or `"exit"`.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        out.push_str("{\n  \"blocks\": [");
+
+        for (idx, block) in self.blocks.iter().enumerate() {
+            let commands = block
+                .commands
+                .iter()
+                .map(|command| format!("\"{command}\""))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let sep = if idx > 0 { "," } else { "" };
+            write!(
+                out,
+                "{sep}\n    {{\"start\": {}, \"commands\": [{commands}]}}",
+                block.start
+            )
+            .unwrap();
+        }
+
+        out.push_str(if self.blocks.is_empty() {
+            "],\n"
+        } else {
+            "\n  ],\n"
+        });
+        out.push_str("  \"edges\": [");
+
+        for (idx, edge) in self.edges.iter().enumerate() {
+            let to = match edge.to {
+                Node::Block(idx) => idx.to_string(),
+                Node::Exit => "\"exit\"".to_string(),
+            };
+            let sep = if idx > 0 { "," } else { "" };
+            write!(
+                out,
+                "{sep}\n    {{\"from\": {}, \"to\": {to}, \"kind\": \"{}\"}}",
+                edge.from,
+                edge.kind.name()
+            )
+            .unwrap();
+        }
+
+        out.push_str(if self.edges.is_empty() {
+            "]\n"
+        } else {
+            "\n  ]\n"
+        });
+        out.push_str("}\n");
+        out
+    }
+}
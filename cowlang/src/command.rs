@@ -1,7 +1,14 @@
-use std::{borrow::Cow, ops::Deref, str::FromStr};
+use std::{
+    borrow::Cow,
+    fmt,
+    io::{self, BufRead},
+    ops::Deref,
+    str::FromStr,
+};
 
 #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Command {
     moo = 0,
     mOo = 1,
@@ -17,6 +24,32 @@ pub enum Command {
     oom = 11,
 }
 
+impl Command {
+    /// A short description of what the command does.
+    pub fn description(self) -> &'static str {
+        match self {
+            Command::moo => "jump back to the matching MOO",
+            Command::mOo => "move the cursor backward",
+            Command::moO => "move the cursor forward",
+            Command::mOO => "evaluate the current value as an instruction",
+            Command::Moo => {
+                "if the current value is 0, read it from stdin, else write it to stdout"
+            }
+            Command::MOo => "decrement the current value",
+            Command::MoO => "increment the current value",
+            Command::MOO => {
+                "if the current value is 0, skip next command and jump to the matching moo"
+            }
+            Command::OOO => "set the current value to 0",
+            Command::MMM => {
+                "if the register is empty, set it to the current value, vice-versa otherwise"
+            }
+            Command::OOM => "write the current value to stdout",
+            Command::oom => "read the current value from stdin",
+        }
+    }
+}
+
 impl FromStr for Command {
     type Err = ();
 
@@ -39,6 +72,12 @@ impl FromStr for Command {
     }
 }
 
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
 impl TryFrom<u32> for Command {
     type Error = ();
 
@@ -61,7 +100,9 @@ impl TryFrom<u32> for Command {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Program<'a>(Cow<'a, [Command]>);
 
 impl<'a> Program<'a> {
@@ -71,12 +112,19 @@ impl<'a> Program<'a> {
 
     pub fn parse(commands: &str) -> Self {
         Self::new(
-            commands
-                .split_whitespace()
-                .filter_map(|s| Command::from_str(s).ok())
+            crate::parse::commands(commands)
+                .map(|spanned| spanned.command)
                 .collect::<Vec<_>>(),
         )
     }
+
+    /// Parses the text from `reader` without holding on to it.
+    pub fn read(reader: impl BufRead) -> io::Result<Self> {
+        let commands = crate::parse::read(reader)
+            .map(|spanned| spanned.map(|spanned| spanned.command))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self::new(commands))
+    }
 }
 
 impl FromStr for Program<'_> {
@@ -87,6 +135,18 @@ impl FromStr for Program<'_> {
     }
 }
 
+impl fmt::Display for Program<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, command) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{command}")?;
+        }
+        Ok(())
+    }
+}
+
 impl Deref for Program<'_> {
     type Target = [Command];
 
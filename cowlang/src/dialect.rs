@@ -0,0 +1,68 @@
+/// Choices on edge cases where COW implementations disagree.
+///
+/// Only the interpreter follows a dialect. The analyses and compilers follow
+/// [`Dialect::REFERENCE`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dialect {
+    pub jumps: Jumps,
+    pub loop_end: LoopEnd,
+    pub recursive_eval: OnRecursiveEval,
+}
+
+impl Dialect {
+    /// The behaviour of the original COW implementation.
+    pub const REFERENCE: Self = Self {
+        jumps: Jumps::Reference,
+        loop_end: LoopEnd::Reevaluate,
+        recursive_eval: OnRecursiveEval::Exit,
+    };
+
+    /// Loops that work like Brainfuck's brackets.
+    pub const STRUCTURED: Self = Self {
+        jumps: Jumps::Matching,
+        loop_end: LoopEnd::Test,
+        recursive_eval: OnRecursiveEval::Error,
+    };
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self::REFERENCE
+    }
+}
+
+/// How `MOO` and `moo` find each other.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Jumps {
+    /// Each search skips the command next to where it starts, and a `moo`
+    /// found right after a `MOO` closes two levels.
+    Reference,
+    /// By nesting alone.
+    Matching,
+}
+
+/// What a `moo` does.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LoopEnd {
+    /// Jump back to the matching `MOO` and evaluate it again, so a current
+    /// value of 0 jumps forward from there.
+    Reevaluate,
+    /// Jump back to just after the matching `MOO` unless the current value is
+    /// 0, in which case continue after the `moo`.
+    Test,
+}
+
+/// What a `mOO` does when the current value is 3, its own number.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OnRecursiveEval {
+    /// Fail with [`Error::RecursiveEval`](crate::Error::RecursiveEval).
+    Error,
+    /// End the program, as the original COW implementation does.
+    Exit,
+    /// Do nothing.
+    Ignore,
+}
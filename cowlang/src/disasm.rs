@@ -0,0 +1,94 @@
+//! Readable listings of programs.
+
+use crate::{Command, jump};
+use std::fmt::Write;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub idx: usize,
+    pub command: Command,
+    /// How many loops the command sits in, counting `MOO … moo` pairs the
+    /// way they read.
+    pub depth: usize,
+    /// Where a `MOO` or `moo` jumps to, as the interpreter would match it.
+    pub jump: Option<Jump>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Jump {
+    /// The index of the matching command.
+    To(usize),
+    /// No command matches, so taking the jump fails.
+    Unmatched,
+}
+
+pub fn entries(program: &[Command]) -> Vec<Entry> {
+    let mut depth = 0usize;
+
+    program
+        .iter()
+        .enumerate()
+        .map(|(idx, &command)| {
+            let target = match command {
+                Command::MOO => Some(jump::forward(program, idx)),
+                Command::moo => {
+                    depth = depth.saturating_sub(1);
+                    Some(jump::backward(program, idx))
+                }
+                _ => None,
+            };
+
+            let entry = Entry {
+                idx,
+                command,
+                depth,
+                jump: target.map(|target| target.map_or(Jump::Unmatched, Jump::To)),
+            };
+
+            if command == Command::MOO {
+                depth += 1;
+            }
+
+            entry
+        })
+        .collect()
+}
+
+/// Lists each command with its index, opcode, jump target and description,
+/// indenting loop bodies.
+pub fn listing(program: &[Command]) -> String {
+    let entries = entries(program);
+
+    let idx_width = entries.len().saturating_sub(1).to_string().len().max(3);
+    let max_depth = entries.iter().map(|entry| entry.depth).max().unwrap_or(0);
+    let command_width = (max_depth * 2 + 3).max("command".len());
+    let jump_width = idx_width + 3;
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{:>idx_width$}  op  {:command_width$}  {:jump_width$}  description",
+        "idx", "command", "jump"
+    )
+    .unwrap();
+
+    for entry in entries {
+        let command = format!("{}{}", "  ".repeat(entry.depth), entry.command);
+        let jump = match entry.jump {
+            Some(Jump::To(idx)) => format!("-> {idx}"),
+            Some(Jump::Unmatched) => "-> ?".to_string(),
+            None => String::new(),
+        };
+
+        writeln!(
+            out,
+            "{:>idx_width$}  {:>2}  {command:command_width$}  {jump:jump_width$}  {}",
+            entry.idx,
+            entry.command as u32,
+            entry.command.description(),
+        )
+        .unwrap();
+    }
+
+    out
+}
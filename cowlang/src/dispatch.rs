@@ -0,0 +1,37 @@
+use crate::Command;
+use std::collections::BTreeMap;
+
+/// How many times each `mOO` has run each command, by the index of the `mOO`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dispatches(BTreeMap<usize, [u64; 12]>);
+
+impl Dispatches {
+    pub fn count(&self, idx: usize, command: Command) -> u64 {
+        self.0
+            .get(&idx)
+            .map_or(0, |counts| counts[command as usize])
+    }
+
+    /// The number of commands run by every `mOO`.
+    pub fn total(&self) -> u64 {
+        self.0.values().flatten().sum()
+    }
+
+    /// Each `mOO` index and command it has run, with how many times.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, Command, u64)> + '_ {
+        self.0.iter().flat_map(|(&idx, counts)| {
+            counts
+                .iter()
+                .enumerate()
+                .filter(|(_, count)| **count > 0)
+                .map(move |(command, &count)| {
+                    (idx, Command::try_from(command as u32).unwrap(), count)
+                })
+        })
+    }
+
+    pub(crate) fn record(&mut self, idx: usize, command: Command) {
+        self.0.entry(idx).or_insert([0; 12])[command as usize] += 1;
+    }
+}
@@ -0,0 +1,181 @@
+//! Canonical layout for COW source.
+//!
+//! Commands are laid out a fixed number to a line, and the body of each
+//! `MOO … moo` loop is indented one level. Loops are nested the way their
+//! commands read, which may differ from how the interpreter matches jumps.
+
+use crate::Command;
+use std::io::{self, BufRead};
+
+#[derive(Debug, Copy, Clone)]
+pub struct Options {
+    /// The most commands put on one line. Lines also break around loops.
+    pub words_per_line: usize,
+    /// The number of spaces each loop level is indented by.
+    pub indent: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            words_per_line: 16,
+            indent: 4,
+        }
+    }
+}
+
+/// Formats a program.
+pub fn program(program: &[Command], options: &Options) -> String {
+    let mut printer = Printer::new(options);
+
+    for &command in program {
+        printer.command(command);
+    }
+
+    printer.finish()
+}
+
+/// Formats source text, keeping its comments.
+///
+/// Everything on a line that isn't a command is a comment. Each run of
+/// comment words is put on a line of its own, with its inner spacing intact,
+/// and a single blank line is kept wherever the source had any.
+pub fn source(source: &str, options: &Options) -> String {
+    let mut printer = Printer::new(options);
+
+    for line in source.lines() {
+        printer.line(line);
+    }
+
+    printer.finish()
+}
+
+/// Formats the source text read from `reader` a line at a time, like
+/// [`source`].
+pub fn read(mut reader: impl BufRead, options: &Options) -> io::Result<String> {
+    let mut printer = Printer::new(options);
+    let mut line = String::new();
+
+    while reader.read_line(&mut line)? > 0 {
+        let text = line
+            .strip_suffix('\n')
+            .map_or(&*line, |line| line.strip_suffix('\r').unwrap_or(line));
+        printer.line(text);
+        line.clear();
+    }
+
+    Ok(printer.finish())
+}
+
+struct Printer<'o> {
+    options: &'o Options,
+    out: String,
+    words: usize,
+    depth: usize,
+    /// Whether the source had a blank line since the last word.
+    pending_blank: bool,
+}
+
+impl<'o> Printer<'o> {
+    fn new(options: &'o Options) -> Self {
+        Self {
+            options,
+            out: String::new(),
+            words: 0,
+            depth: 0,
+            pending_blank: false,
+        }
+    }
+
+    /// Prints a line of source, keeping its comments.
+    fn line(&mut self, line: &str) {
+        let mut comment: Option<(usize, usize)> = None;
+
+        for word in line.split_whitespace() {
+            if self.pending_blank {
+                self.blank();
+                self.pending_blank = false;
+            }
+
+            let start = word.as_ptr() as usize - line.as_ptr() as usize;
+            let end = start + word.len();
+
+            match word.parse::<Command>() {
+                Ok(command) => {
+                    if let Some((start, end)) = comment.take() {
+                        self.comment(&line[start..end]);
+                    }
+                    self.command(command);
+                }
+                Err(()) => {
+                    let start = comment.map_or(start, |(start, _)| start);
+                    comment = Some((start, end));
+                }
+            }
+        }
+
+        if let Some((start, end)) = comment {
+            self.comment(&line[start..end]);
+        }
+
+        if line.trim().is_empty() && !self.out.is_empty() {
+            self.pending_blank = true;
+        }
+    }
+
+    fn command(&mut self, command: Command) {
+        if command == Command::moo {
+            self.break_line();
+            self.depth = self.depth.saturating_sub(1);
+        }
+
+        if self.words >= self.options.words_per_line.max(1) {
+            self.break_line();
+        }
+
+        if self.words == 0 {
+            self.indent();
+        } else {
+            self.out.push(' ');
+        }
+        self.out.push_str(&command.to_string());
+        self.words += 1;
+
+        if command == Command::MOO {
+            self.break_line();
+            self.depth += 1;
+        }
+    }
+
+    fn comment(&mut self, text: &str) {
+        self.break_line();
+        self.indent();
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn blank(&mut self) {
+        self.break_line();
+
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn indent(&mut self) {
+        let width = self.depth * self.options.indent;
+        self.out.extend(std::iter::repeat_n(' ', width));
+    }
+
+    fn break_line(&mut self) {
+        if self.words > 0 {
+            self.out.push('\n');
+            self.words = 0;
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.break_line();
+        self.out
+    }
+}
@@ -0,0 +1,129 @@
+//! Generates programs that print a given text.
+
+use crate::{Builder, Command, Program};
+use std::fmt;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Style {
+    /// Sets up cells near the values needed with a multiplication loop and
+    /// prints each character from the closest one, keeping the shortest of
+    /// the layouts tried.
+    #[default]
+    Compact,
+    /// Walks a single cell from one character to the next, so each `Moo` is
+    /// preceded by the plain increments or decrements that produce it.
+    Readable,
+}
+
+/// Generates a program that prints `text`.
+///
+/// A `Moo` on a cell holding 0 reads rather than writes, so text containing
+/// NUL cannot be printed.
+pub fn print(text: &str, style: Style) -> Result<Program<'static>, ContainsNul> {
+    if let Some(idx) = text.find('\0') {
+        return Err(ContainsNul { idx });
+    }
+
+    let codes = text.chars().map(|char| char as u32).collect::<Vec<_>>();
+
+    Ok(match style {
+        Style::Compact => (2..=64)
+            .map(|multiplier| compact(&codes, multiplier))
+            .chain([readable(&codes)])
+            .min_by_key(|program| program.len())
+            .unwrap(),
+        Style::Readable => readable(&codes),
+    })
+}
+
+fn readable(codes: &[u32]) -> Program<'static> {
+    let mut builder = Builder::new();
+    let mut value = 0;
+
+    for &code in codes {
+        adjust(&mut builder, value, code);
+        builder.push(Command::Moo);
+        value = code;
+    }
+
+    builder.build()
+}
+
+/// Fills a cell for each multiple of `multiplier` close to a character, then
+/// prints every character from whichever cell is cheapest to reach and adjust.
+fn compact(codes: &[u32], multiplier: u32) -> Program<'static> {
+    let mut multiples = codes
+        .iter()
+        .map(|code| (code + multiplier / 2) / multiplier)
+        .filter(|&multiple| multiple > 0)
+        .collect::<Vec<_>>();
+    multiples.sort_unstable();
+    multiples.dedup();
+
+    let mut builder = Builder::new();
+    let mut values = vec![0];
+
+    // cell 0 counts down while the loop adds to the cells after it
+    if !multiples.is_empty() {
+        builder.extend(repeat(Command::MoO, multiplier));
+        builder.push(Command::MOO);
+
+        for &multiple in &multiples {
+            builder.push(Command::moO);
+            builder.extend(repeat(Command::MoO, multiple));
+            values.push(multiple * multiplier);
+        }
+
+        builder.extend(repeat(Command::mOo, multiples.len() as u32));
+        builder.extend([Command::MOo, Command::moo]);
+    }
+
+    let mut position = 0;
+
+    for &code in codes {
+        let cell = (0..values.len())
+            .min_by_key(|&cell| cell.abs_diff(position) as u64 + values[cell].abs_diff(code) as u64)
+            .unwrap();
+
+        let command = if cell > position {
+            Command::moO
+        } else {
+            Command::mOo
+        };
+        builder.extend(repeat(command, cell.abs_diff(position) as u32));
+        position = cell;
+
+        adjust(&mut builder, values[cell], code);
+        builder.push(Command::Moo);
+        values[cell] = code;
+    }
+
+    builder.build()
+}
+
+fn adjust(builder: &mut Builder, from: u32, to: u32) {
+    let command = if to > from {
+        Command::MoO
+    } else {
+        Command::MOo
+    };
+    builder.extend(repeat(command, from.abs_diff(to)));
+}
+
+fn repeat(command: Command, count: u32) -> impl Iterator<Item = Command> {
+    std::iter::repeat_n(command, count as usize)
+}
+
+/// The text holds a NUL at this byte index.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ContainsNul {
+    pub idx: usize,
+}
+
+impl std::error::Error for ContainsNul {}
+
+impl fmt::Display for ContainsNul {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot print the NUL at byte {}", self.idx)
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    Program,
+    batch::{self, Case, Job, Outcome},
+};
+use std::{fs, io, path::Path};
+
+/// Finds every `<name>.txt` program in `dir` that has a `<name>.out` file
+/// holding its expected output.
+///
+/// The program reads its input from `<name>.in` when present. A `<name>.steps`
+/// file holding a step count marks a program that never terminates, and its
+/// output is compared after that many steps instead.
+pub fn discover(dir: &Path) -> io::Result<Vec<Job<'static>>> {
+    let mut jobs = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.extension().is_none_or(|ext| ext != "txt") {
+            continue;
+        }
+
+        let Some(expected) = read_optional(&path.with_extension("out"))? else {
+            continue;
+        };
+
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let input = read_optional(&path.with_extension("in"))?.unwrap_or_default();
+
+        let max_steps = read_optional(&path.with_extension("steps"))?
+            .map(|steps| {
+                steps
+                    .trim()
+                    .parse()
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+            })
+            .transpose()?;
+
+        jobs.push(Job {
+            program: Program::read(io::BufReader::new(fs::File::open(&path)?))?,
+            case: Case {
+                name: name.clone(),
+                input,
+                expected,
+                max_steps,
+            },
+            name,
+        });
+    }
+
+    jobs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(jobs)
+}
+
+pub fn run(dir: &Path, options: &batch::Options) -> io::Result<Vec<Outcome>> {
+    Ok(batch::run(&discover(dir)?, options))
+}
+
+fn read_optional(path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
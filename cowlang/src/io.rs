@@ -1,4 +1,4 @@
-use std::io;
+use std::io::{self, BufRead, Write};
 
 pub trait Input {
     fn input_int(&mut self) -> io::Result<u32>;
@@ -9,3 +9,170 @@ pub trait Output {
     fn output_int(&mut self, int: u32) -> io::Result<()>;
     fn output_char(&mut self, char: char) -> io::Result<()>;
 }
+
+impl<T: Input + ?Sized> Input for &mut T {
+    fn input_int(&mut self) -> io::Result<u32> {
+        (**self).input_int()
+    }
+
+    fn input_char(&mut self) -> io::Result<char> {
+        (**self).input_char()
+    }
+}
+
+impl<T: Input + ?Sized> Input for Box<T> {
+    fn input_int(&mut self) -> io::Result<u32> {
+        (**self).input_int()
+    }
+
+    fn input_char(&mut self) -> io::Result<char> {
+        (**self).input_char()
+    }
+}
+
+impl<T: Output + ?Sized> Output for &mut T {
+    fn output_int(&mut self, int: u32) -> io::Result<()> {
+        (**self).output_int(int)
+    }
+
+    fn output_char(&mut self, char: char) -> io::Result<()> {
+        (**self).output_char(char)
+    }
+}
+
+impl<T: Output + ?Sized> Output for Box<T> {
+    fn output_int(&mut self, int: u32) -> io::Result<()> {
+        (**self).output_int(int)
+    }
+
+    fn output_char(&mut self, char: char) -> io::Result<()> {
+        (**self).output_char(char)
+    }
+}
+
+/// An [`Input`] reading from any [`BufRead`].
+///
+/// Chars are decoded as UTF-8 one at a time, while ints are read as a whole
+/// line holding a decimal number.
+#[derive(Debug)]
+pub struct Reader<R> {
+    inner: R,
+}
+
+impl<R> Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BufRead> Input for Reader<R> {
+    fn input_int(&mut self) -> io::Result<u32> {
+        let mut line = String::new();
+
+        if self.inner.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        line.trim()
+            .parse()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    fn input_char(&mut self) -> io::Result<char> {
+        let mut buf = [0; 4];
+        self.inner.read_exact(&mut buf[..1])?;
+
+        let len = match buf[0] {
+            0x00..=0x7F => 1,
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => return Err(io::ErrorKind::InvalidData.into()),
+        };
+
+        self.inner.read_exact(&mut buf[1..len])?;
+
+        std::str::from_utf8(&buf[..len])
+            .ok()
+            .and_then(|s| s.chars().next())
+            .ok_or_else(|| io::ErrorKind::InvalidData.into())
+    }
+}
+
+/// An [`Output`] writing to any [`Write`]. Ints are written in decimal with
+/// no separator.
+#[derive(Debug)]
+pub struct Writer<W> {
+    inner: W,
+}
+
+impl<W> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Output for Writer<W> {
+    fn output_int(&mut self, int: u32) -> io::Result<()> {
+        write!(self.inner, "{int}")
+    }
+
+    fn output_char(&mut self, char: char) -> io::Result<()> {
+        write!(self.inner, "{char}")
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputKind {
+    Int,
+    Char,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Value {
+    Int(u32),
+    Char(char),
+}
+
+impl Value {
+    pub fn kind(self) -> InputKind {
+        match self {
+            Self::Int(_) => InputKind::Int,
+            Self::Char(_) => InputKind::Char,
+        }
+    }
+}
+
+/// An [`Input`] that never has a value ready.
+///
+/// Reading from it fails with [`io::ErrorKind::WouldBlock`], which makes the
+/// interpreter suspend with [`Status::NeedsInput`](crate::Status::NeedsInput)
+/// until the value is supplied through
+/// [`Cowlang::provide_input`](crate::Cowlang::provide_input). Any other input
+/// source may do the same to signal that it has nothing buffered yet.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Suspend;
+
+impl Input for Suspend {
+    fn input_int(&mut self) -> io::Result<u32> {
+        Err(io::ErrorKind::WouldBlock.into())
+    }
+
+    fn input_char(&mut self) -> io::Result<char> {
+        Err(io::ErrorKind::WouldBlock.into())
+    }
+}
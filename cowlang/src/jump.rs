@@ -0,0 +1,92 @@
+use crate::Command;
+
+/// Finds the `moo` matching a `MOO` at `idx`, the way the interpreter does
+/// when the current value is 0. The command right after `idx` is skipped, and
+/// a `moo` directly after a `MOO` closes two levels.
+pub(crate) fn forward(program: &[Command], idx: usize) -> Option<usize> {
+    #[allow(non_snake_case)]
+    let mut unmatched_MOOs = 1;
+    let mut idx = idx.saturating_add(1);
+
+    while unmatched_MOOs > 0 {
+        let prev_command = *program.get(idx)?;
+        idx += 1;
+
+        match program.get(idx) {
+            Some(Command::moo) => {
+                unmatched_MOOs -= 1;
+
+                if matches!(prev_command, Command::MOO) {
+                    unmatched_MOOs -= 1;
+                }
+            }
+            Some(Command::MOO) => {
+                unmatched_MOOs += 1;
+            }
+            _ => {}
+        }
+    }
+
+    Some(idx)
+}
+
+/// Finds the `MOO` matching a `moo` at `idx`, skipping the command right
+/// before it.
+pub(crate) fn backward(program: &[Command], idx: usize) -> Option<usize> {
+    let mut unmatched_moos = 1;
+    let mut idx = idx.saturating_sub(1);
+
+    while unmatched_moos > 0 {
+        if idx == 0 {
+            return None;
+        }
+
+        idx -= 1;
+
+        match program[idx] {
+            Command::moo => {
+                unmatched_moos += 1;
+            }
+            Command::MOO => {
+                unmatched_moos -= 1;
+            }
+            _ => {}
+        }
+    }
+
+    Some(idx)
+}
+
+/// Finds the `moo` matching a `MOO` at `idx` by nesting alone, with no
+/// commands skipped.
+pub(crate) fn matching_forward(program: &[Command], idx: usize) -> Option<usize> {
+    let mut depth = 0usize;
+
+    for (idx, &command) in program.iter().enumerate().skip(idx + 1) {
+        match command {
+            Command::MOO => depth += 1,
+            Command::moo if depth == 0 => return Some(idx),
+            Command::moo => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Finds the `MOO` matching a `moo` at `idx` by nesting alone, with no
+/// commands skipped.
+pub(crate) fn matching_backward(program: &[Command], idx: usize) -> Option<usize> {
+    let mut depth = 0usize;
+
+    for (idx, &command) in program[..idx].iter().enumerate().rev() {
+        match command {
+            Command::moo => depth += 1,
+            Command::MOO if depth == 0 => return Some(idx),
+            Command::MOO => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
@@ -1,29 +1,66 @@
+pub mod analysis;
+pub mod asm;
+pub mod backend;
+pub mod batch;
+pub mod binary;
+pub mod brainfuck;
+mod builder;
+pub mod cfg;
 mod command;
+mod dialect;
+pub mod disasm;
+mod dispatch;
+pub mod format;
+pub mod generate;
+pub mod golden;
 mod io;
+mod jump;
+pub mod lint;
+pub mod optimize;
+pub mod parse;
+pub mod preprocess;
+pub mod script;
+mod snapshot;
 
 pub use self::{
+    builder::Builder,
     command::{Command, Program},
-    io::{Input, Output},
+    dialect::{Dialect, Jumps, LoopEnd, OnRecursiveEval},
+    dispatch::Dispatches,
+    io::{Input, InputKind, Output, Reader, Suspend, Value, Writer},
+    snapshot::Snapshot,
 };
-
-pub struct Cowlang<'a> {
+use std::ops::ControlFlow;
+
+/// A COW interpreter.
+///
+/// By default the interpreter borrows its input and output. Any owned
+/// [`Input`] and [`Output`] may be used instead, in which case a
+/// `Cowlang<'static, I, O>` owns everything it needs and is [`Send`] whenever
+/// `I` and `O` are, so it can be moved onto another thread or task.
+pub struct Cowlang<'a, I = &'a mut dyn Input, O = &'a mut dyn Output> {
     memory: Vec<u32>,
     memory_idx: usize,
     program: Program<'a>,
     program_idx: usize,
-    input: &'a mut dyn Input,
-    output: &'a mut dyn Output,
+    input: I,
+    output: O,
     register: Option<u32>,
+    awaiting_input: Option<InputKind>,
+    pending_input: Option<Value>,
+    dispatches: Dispatches,
+    dialect: Dialect,
 }
 
-pub struct Options<'a> {
+pub struct Options<'a, I = &'a mut dyn Input, O = &'a mut dyn Output> {
     pub program: Program<'a>,
-    pub input: &'a mut dyn Input,
-    pub output: &'a mut dyn Output,
+    pub input: I,
+    pub output: O,
+    pub dialect: Dialect,
 }
 
-impl<'a> Cowlang<'a> {
-    pub fn new(options: Options<'a>) -> Self {
+impl<'a, I: Input, O: Output> Cowlang<'a, I, O> {
+    pub fn new(options: Options<'a, I, O>) -> Self {
         Self {
             memory: vec![0],
             memory_idx: 0,
@@ -32,6 +69,10 @@ impl<'a> Cowlang<'a> {
             input: options.input,
             output: options.output,
             register: None,
+            awaiting_input: None,
+            pending_input: None,
+            dispatches: Dispatches::default(),
+            dialect: options.dialect,
         }
     }
 
@@ -43,6 +84,10 @@ impl<'a> Cowlang<'a> {
         self.memory_idx
     }
 
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
     pub fn program(&self) -> &[Command] {
         &self.program
     }
@@ -63,59 +108,145 @@ impl<'a> Cowlang<'a> {
         self.register
     }
 
+    /// The commands each `mOO` has run so far.
+    pub fn dispatches(&self) -> &Dispatches {
+        &self.dispatches
+    }
+
+    pub fn input(&self) -> &I {
+        &self.input
+    }
+
+    pub fn input_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    pub fn output(&self) -> &O {
+        &self.output
+    }
+
+    pub fn output_mut(&mut self) -> &mut O {
+        &mut self.output
+    }
+
+    pub fn into_io(self) -> (I, O) {
+        (self.input, self.output)
+    }
+
     pub fn completed(&self) -> bool {
         self.program_idx >= self.program.len()
     }
 
-    pub fn run(&mut self) -> Result<(), Error> {
-        while !self.completed() {
-            self.advance()?;
+    pub fn awaiting_input(&self) -> Option<InputKind> {
+        self.awaiting_input
+    }
+
+    pub fn status(&self) -> Status {
+        if let Some(kind) = self.awaiting_input {
+            Status::NeedsInput(kind)
+        } else if self.completed() {
+            Status::Completed
+        } else {
+            Status::Running
         }
+    }
+
+    /// Supplies the value the interpreter is waiting for after returning
+    /// [`Status::NeedsInput`]. The suspended command consumes it when
+    /// execution is resumed.
+    pub fn provide_input(&mut self, value: Value) -> Result<(), Error> {
+        if self.awaiting_input != Some(value.kind()) {
+            return Err(Error::UnexpectedInput);
+        }
+
+        self.awaiting_input = None;
+        self.pending_input = Some(value);
         Ok(())
     }
 
-    pub fn advance(&mut self) -> Result<(), Error> {
+    pub fn run(&mut self) -> Result<Status, Error> {
+        while !self.completed() {
+            if let Status::NeedsInput(kind) = self.advance()? {
+                return Ok(Status::NeedsInput(kind));
+            }
+        }
+        Ok(Status::Completed)
+    }
+
+    pub fn advance(&mut self) -> Result<Status, Error> {
+        if self.awaiting_input.is_some() {
+            return Ok(self.status());
+        }
+
         if let Some(&command) = self.program.get(self.program_idx) {
-            self.evaluate(command)?;
+            if let ControlFlow::Break(kind) = self.evaluate(command)? {
+                return Ok(Status::NeedsInput(kind));
+            }
             self.program_idx += 1;
         }
-        Ok(())
+        Ok(self.status())
     }
 
-    fn evaluate(&mut self, command: Command) -> Result<(), Error> {
+    fn read(&mut self, kind: InputKind) -> Result<ControlFlow<InputKind, u32>, Error> {
+        if let Some(value) = self.pending_input.take() {
+            return Ok(ControlFlow::Continue(match value {
+                Value::Int(int) => int,
+                Value::Char(char) => char as u32,
+            }));
+        }
+
+        let result = match kind {
+            InputKind::Int => self.input.input_int(),
+            InputKind::Char => self.input.input_char().map(|char| char as u32),
+        };
+
+        match result {
+            Ok(value) => Ok(ControlFlow::Continue(value)),
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                self.awaiting_input = Some(kind);
+                Ok(ControlFlow::Break(kind))
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn evaluate(&mut self, command: Command) -> Result<ControlFlow<InputKind>, Error> {
         macro_rules! value {
             () => {
                 self.memory[self.memory_idx]
             };
         }
 
+        macro_rules! read {
+            ($kind:expr) => {
+                match self.read($kind)? {
+                    ControlFlow::Continue(value) => value,
+                    ControlFlow::Break(kind) => return Ok(ControlFlow::Break(kind)),
+                }
+            };
+        }
+
         match command {
             Command::moo => {
-                self.program_idx = self.program_idx.saturating_sub(1);
-
-                let mut unmatched_moos = 1;
-
-                while unmatched_moos > 0 {
-                    if self.program_idx == 0 {
-                        return Err(Error::BeginlessJumpBackward);
+                let start = match self.dialect.jumps {
+                    Jumps::Reference => crate::jump::backward(&self.program, self.program_idx),
+                    Jumps::Matching => {
+                        crate::jump::matching_backward(&self.program, self.program_idx)
                     }
+                }
+                .ok_or(Error::BeginlessJumpBackward)?;
 
-                    self.program_idx -= 1;
-
-                    match self.current_instruction() {
-                        Some(Command::moo) => {
-                            unmatched_moos += 1;
-                        }
-                        Some(Command::MOO) => {
-                            unmatched_moos -= 1;
+                match self.dialect.loop_end {
+                    LoopEnd::Reevaluate => {
+                        self.program_idx = start;
+                        return self.evaluate(Command::MOO);
+                    }
+                    LoopEnd::Test => {
+                        if value!() != 0 {
+                            self.program_idx = start;
                         }
-                        _ => {}
                     }
                 }
-
-                if let Some(command) = self.current_instruction() {
-                    self.evaluate(command)?;
-                }
             }
             Command::mOo => {
                 self.memory_idx = self.memory_idx.saturating_sub(1);
@@ -131,22 +262,34 @@ impl<'a> Cowlang<'a> {
                 let value = value!();
 
                 if value == Command::mOO as u32 {
-                    return Err(Error::RecursiveEval);
+                    match self.dialect.recursive_eval {
+                        OnRecursiveEval::Error => return Err(Error::RecursiveEval),
+                        // `advance` moves past the last command
+                        OnRecursiveEval::Exit => self.program_idx = self.program.len() - 1,
+                        OnRecursiveEval::Ignore => {}
+                    }
+                    return Ok(ControlFlow::Continue(()));
                 }
                 let Ok(executed_command) = Command::try_from(value) else {
                     return Err(Error::InvalidCommand);
                 };
 
-                self.evaluate(executed_command)?;
+                // a suspended command is evaluated again once input arrives
+                let idx = self.program_idx;
+                let flow = self.evaluate(executed_command)?;
+                if flow.is_continue() {
+                    self.dispatches.record(idx, executed_command);
+                }
+                return Ok(flow);
             }
             Command::Moo => {
-                let value = &mut value!();
+                let value = value!();
 
-                if *value == 0 {
-                    *value = self.input.input_char()? as u32;
+                if value == 0 {
+                    value!() = read!(InputKind::Char);
                 } else {
                     self.output
-                        .output_char(char::from_u32(*value).ok_or(Error::UnwritableChar)?)?;
+                        .output_char(char::from_u32(value).ok_or(Error::UnwritableChar)?)?;
                 }
             }
             Command::MOo => {
@@ -157,34 +300,13 @@ impl<'a> Cowlang<'a> {
             }
             Command::MOO => {
                 if value!() == 0 {
-                    #[allow(non_snake_case)]
-                    let mut unmatched_MOOs = 1;
-                    let mut prev_command;
-
-                    self.program_idx = self.program_idx.saturating_add(1);
-
-                    while unmatched_MOOs > 0 {
-                        let Some(command) = self.current_instruction() else {
-                            return Err(Error::EndlessJumpForward);
-                        };
-
-                        prev_command = command;
-                        self.program_idx += 1;
-
-                        match self.current_instruction() {
-                            Some(Command::moo) => {
-                                unmatched_MOOs -= 1;
-
-                                if matches!(prev_command, Command::MOO) {
-                                    unmatched_MOOs -= 1;
-                                }
-                            }
-                            Some(Command::MOO) => {
-                                unmatched_MOOs += 1;
-                            }
-                            _ => {}
+                    self.program_idx = match self.dialect.jumps {
+                        Jumps::Reference => crate::jump::forward(&self.program, self.program_idx),
+                        Jumps::Matching => {
+                            crate::jump::matching_forward(&self.program, self.program_idx)
                         }
                     }
+                    .ok_or(Error::EndlessJumpForward)?;
                 }
             }
             Command::OOO => {
@@ -202,21 +324,31 @@ impl<'a> Cowlang<'a> {
                 self.output.output_int(value!())?;
             }
             Command::oom => {
-                value!() = self.input.input_int()?;
+                value!() = read!(InputKind::Int);
             }
         }
 
-        Ok(())
+        Ok(ControlFlow::Continue(()))
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Status {
+    Running,
+    NeedsInput(InputKind),
+    Completed,
+}
+
 #[derive(Debug)]
 pub enum Error {
     BeginlessJumpBackward,
     EndlessJumpForward,
     InvalidCommand,
     RecursiveEval,
+    UnexpectedInput,
     UnwritableChar,
+    InvalidSnapshot,
     Io(std::io::Error),
 }
 
@@ -229,7 +361,9 @@ impl std::fmt::Display for Error {
             Self::EndlessJumpForward => write!(f, "endless jump forward"),
             Self::InvalidCommand => write!(f, "invalid command"),
             Self::RecursiveEval => write!(f, "recursive evaluation"),
+            Self::UnexpectedInput => write!(f, "unexpected input"),
             Self::UnwritableChar => write!(f, "unwritable char"),
+            Self::InvalidSnapshot => write!(f, "invalid snapshot"),
             Self::Io(error) => write!(f, "{error}"),
         }
     }
@@ -240,3 +374,52 @@ impl From<std::io::Error> for Error {
         Self::Io(error)
     }
 }
+
+/// Errors are serialized like a derived enum, with an I/O error replaced by
+/// its message.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "Error")]
+enum SerializedError {
+    BeginlessJumpBackward,
+    EndlessJumpForward,
+    InvalidCommand,
+    RecursiveEval,
+    UnexpectedInput,
+    UnwritableChar,
+    InvalidSnapshot,
+    Io(String),
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Error {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::BeginlessJumpBackward => SerializedError::BeginlessJumpBackward,
+            Self::EndlessJumpForward => SerializedError::EndlessJumpForward,
+            Self::InvalidCommand => SerializedError::InvalidCommand,
+            Self::RecursiveEval => SerializedError::RecursiveEval,
+            Self::UnexpectedInput => SerializedError::UnexpectedInput,
+            Self::UnwritableChar => SerializedError::UnwritableChar,
+            Self::InvalidSnapshot => SerializedError::InvalidSnapshot,
+            Self::Io(error) => SerializedError::Io(error.to_string()),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Error {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match SerializedError::deserialize(deserializer)? {
+            SerializedError::BeginlessJumpBackward => Self::BeginlessJumpBackward,
+            SerializedError::EndlessJumpForward => Self::EndlessJumpForward,
+            SerializedError::InvalidCommand => Self::InvalidCommand,
+            SerializedError::RecursiveEval => Self::RecursiveEval,
+            SerializedError::UnexpectedInput => Self::UnexpectedInput,
+            SerializedError::UnwritableChar => Self::UnwritableChar,
+            SerializedError::InvalidSnapshot => Self::InvalidSnapshot,
+            SerializedError::Io(message) => Self::Io(std::io::Error::other(message)),
+        })
+    }
+}
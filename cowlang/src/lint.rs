@@ -0,0 +1,116 @@
+//! Warns about likely bugs, using [`Program::analyze`] to follow every path a
+//! program may take. Input may produce any value, so warnings are about what
+//! may happen, not what will.
+
+use crate::{Command, Program, jump};
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Lint {
+    pub idx: usize,
+    pub kind: LintKind,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintKind {
+    /// A `mOo` that may run with the cursor on the first cell, where it does
+    /// nothing.
+    MoveBeforeStart,
+    /// A `moo` that is only ever reached with a nonzero value, so the loop it
+    /// closes never ends.
+    EndlessLoop,
+    /// A `mOO` that may evaluate 3.
+    RecursiveEval,
+    /// A `mOO` that may evaluate a value above 11.
+    InvalidCommand,
+    /// This many commands that are never run.
+    Unreachable(usize),
+    /// A `MMM` whose value may still be in the register when the program ends.
+    DanglingRegister,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.idx, self.kind)
+    }
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MoveBeforeStart => write!(f, "`mOo` may run on the first cell and do nothing"),
+            Self::EndlessLoop => write!(f, "loop never ends, as the value is never 0 here"),
+            Self::RecursiveEval => write!(f, "`mOO` may evaluate 3, which ends the program"),
+            Self::InvalidCommand => {
+                write!(f, "`mOO` may evaluate a value above 11, which is an error")
+            }
+            Self::Unreachable(1) => write!(f, "1 command is never run"),
+            Self::Unreachable(count) => write!(f, "{count} commands are never run"),
+            Self::DanglingRegister => {
+                write!(f, "`MMM` may leave a value in the register at exit")
+            }
+        }
+    }
+}
+
+/// Lints `program`, returning warnings ordered by index.
+pub fn lint(program: &[Command]) -> Vec<Lint> {
+    let analysis = Program::new(program).analyze();
+    let mut lints = Vec::new();
+
+    for (idx, &command) in program.iter().enumerate() {
+        let Some(point) = analysis.point(idx) else {
+            continue;
+        };
+        let mut lint = |kind| lints.push(Lint { idx, kind });
+        let at_start = point.offset() == Some(0);
+
+        match command {
+            Command::mOo if at_start => lint(LintKind::MoveBeforeStart),
+            Command::moo
+                if jump::backward(program, idx).is_some() && point.current().is_nonzero() =>
+            {
+                lint(LintKind::EndlessLoop)
+            }
+            Command::mOO => {
+                let dispatch = point.dispatch();
+
+                if at_start && dispatch.commands.contains(&Command::mOo) {
+                    lint(LintKind::MoveBeforeStart);
+                }
+                if dispatch.may_recurse {
+                    lint(LintKind::RecursiveEval);
+                }
+                if dispatch.may_be_invalid {
+                    lint(LintKind::InvalidCommand);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut idx = 0;
+    while idx < program.len() {
+        let count = (idx..program.len())
+            .take_while(|&idx| !analysis.is_reachable(idx))
+            .count();
+        if count > 0 {
+            lints.push(Lint {
+                idx,
+                kind: LintKind::Unreachable(count),
+            });
+        }
+        idx += count.max(1);
+    }
+
+    if let Some(exit) = analysis.exit() {
+        lints.extend(exit.register_filled_by().map(|idx| Lint {
+            idx,
+            kind: LintKind::DanglingRegister,
+        }));
+    }
+
+    lints.sort();
+    lints.dedup();
+    lints
+}
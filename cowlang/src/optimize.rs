@@ -0,0 +1,319 @@
+//! A peephole optimizer that shortens programs without changing what they do,
+//! unless [`Options`] allows it to.
+//!
+//! Every rewrite is checked against the interpreter's jump matching: it is
+//! only kept if each remaining `MOO`, `moo` and `mOO` still jumps to the same
+//! command as before, since the skipped neighbours and nesting counts that
+//! matching depends on are easily disturbed by removing commands.
+
+use crate::{Command, Program, jump};
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Options {
+    /// Removes `MoO MOo` pairs. These change the current value when it is
+    /// `u32::MAX`, where the increment saturates, so this is off by default.
+    pub cancel_increments: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Optimized {
+    pub program: Program<'static>,
+    pub transformations: Vec<Transformation>,
+}
+
+/// A rewrite applied to the program, at an index into the original program.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Transformation {
+    pub idx: usize,
+    pub kind: TransformationKind,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TransformationKind {
+    /// Removed `MoO MOo`.
+    CancelledIncrement,
+    /// Removed `moO mOo`.
+    CancelledMove,
+    /// Replaced `MOO MOo moo` with `OOO`.
+    ClearLoop,
+    /// Removed this many commands after a loop that never ends.
+    DeadCode(usize),
+}
+
+impl fmt::Display for Transformation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let idx = self.idx;
+
+        match self.kind {
+            TransformationKind::CancelledIncrement => write!(f, "{idx}: removed `MoO MOo`"),
+            TransformationKind::CancelledMove => write!(f, "{idx}: removed `moO mOo`"),
+            TransformationKind::ClearLoop => {
+                write!(f, "{idx}: replaced `MOO MOo moo` with `OOO`")
+            }
+            TransformationKind::DeadCode(count) => write!(
+                f,
+                "{idx}: removed {count} unreachable commands after a loop that never ends"
+            ),
+        }
+    }
+}
+
+pub fn optimize(program: &[Command], options: &Options) -> Optimized {
+    let mut commands = program.to_vec();
+    // the index in `program` of each command in `commands`
+    let mut origin = (0..program.len()).collect::<Vec<_>>();
+    let mut transformations = Vec::new();
+
+    loop {
+        let mut rewrites = find(&commands, options);
+        if rewrites.is_empty() {
+            break;
+        }
+
+        let targets = (0..commands.len())
+            .map(|idx| target(&commands, idx))
+            .collect::<Vec<_>>();
+
+        let (next, map) = loop {
+            let (next, map) = apply(&commands, &rewrites);
+            let moved = moved(&targets, &next, &map);
+            if moved.is_empty() {
+                break (next, map);
+            }
+
+            // drop the rewrites most likely to blame, and check the rest
+            // again
+            let disturbing = rewrites
+                .iter()
+                .map(|rewrite| disturbs(rewrite, &next, &map))
+                .collect::<Vec<_>>();
+            let len = rewrites.len();
+
+            for narrow in [true, false] {
+                let mut disturbing = disturbing.iter();
+                rewrites.retain(|rewrite| {
+                    !disturbing.next().unwrap()
+                        || moved.iter().all(|jump| {
+                            let (start, end) = jump.range(&commands, narrow);
+                            rewrite.idx > end || rewrite.idx + rewrite.len() <= start
+                        })
+                });
+                if rewrites.len() < len {
+                    break;
+                }
+            }
+        };
+
+        if rewrites.is_empty() {
+            break;
+        }
+
+        transformations.extend(rewrites.iter().map(|rewrite| Transformation {
+            idx: origin[rewrite.idx],
+            kind: rewrite.kind,
+        }));
+
+        origin = map.iter().map(|&idx| origin[idx]).collect();
+        commands = next;
+    }
+
+    transformations.sort_by_key(|transformation| transformation.idx);
+
+    Optimized {
+        program: Program::new(commands),
+        transformations,
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Rewrite {
+    idx: usize,
+    kind: TransformationKind,
+}
+
+impl Rewrite {
+    fn len(&self) -> usize {
+        match self.kind {
+            TransformationKind::CancelledIncrement | TransformationKind::CancelledMove => 2,
+            TransformationKind::ClearLoop => 3,
+            TransformationKind::DeadCode(count) => count,
+        }
+    }
+}
+
+/// Finds non-overlapping rewrites, from left to right.
+fn find(commands: &[Command], options: &Options) -> Vec<Rewrite> {
+    let mut rewrites = Vec::new();
+    let mut idx = 0;
+
+    let dead_code = dead_code(commands);
+    let end = dead_code.map_or(commands.len(), |rewrite| rewrite.idx);
+
+    while idx < end {
+        let kind = match commands[idx..end] {
+            [Command::MoO, Command::MOo, ..] if options.cancel_increments => {
+                Some(TransformationKind::CancelledIncrement)
+            }
+            [Command::moO, Command::mOo, ..] => Some(TransformationKind::CancelledMove),
+            [Command::MOO, Command::MOo, Command::moo, ..] => Some(TransformationKind::ClearLoop),
+            _ => None,
+        };
+
+        match kind {
+            Some(kind) => {
+                let rewrite = Rewrite { idx, kind };
+                idx += rewrite.len();
+                rewrites.push(rewrite);
+            }
+            None => idx += 1,
+        }
+    }
+
+    rewrites.extend(dead_code);
+    rewrites
+}
+
+/// Finds the commands after the first loop that can never end once entered
+/// and is always entered when reached: one whose body leaves the cursor and
+/// the current value, just made nonzero by a `MoO`, alone.
+fn dead_code(commands: &[Command]) -> Option<Rewrite> {
+    for (idx, pair) in commands.windows(2).enumerate() {
+        if pair != [Command::MoO, Command::MOO] {
+            continue;
+        }
+
+        let start = idx + 1;
+        let Some(end) = jump::forward(commands, start) else {
+            continue;
+        };
+
+        let body_is_inert = commands[start + 1..end]
+            .iter()
+            .all(|command| matches!(command, Command::MoO | Command::OOM | Command::Moo));
+
+        // no earlier jump may lead past the loop
+        let reachable_only_through = commands[..start].iter().enumerate().all(|(idx, &command)| {
+            command != Command::MOO || jump::forward(commands, idx).is_none_or(|end| end < start)
+        });
+
+        if body_is_inert
+            && jump::backward(commands, end) == Some(start)
+            && reachable_only_through
+            && end + 1 < commands.len()
+        {
+            return Some(Rewrite {
+                idx: end + 1,
+                kind: TransformationKind::DeadCode(commands.len() - end - 1),
+            });
+        }
+    }
+
+    None
+}
+
+/// Applies `rewrites`, returning the new commands and the index each came
+/// from.
+fn apply(commands: &[Command], rewrites: &[Rewrite]) -> (Vec<Command>, Vec<usize>) {
+    let mut next = Vec::with_capacity(commands.len());
+    let mut map = Vec::with_capacity(commands.len());
+    let mut rewrites = rewrites.iter().peekable();
+    let mut idx = 0;
+
+    while idx < commands.len() {
+        if let Some(rewrite) = rewrites.next_if(|rewrite| rewrite.idx == idx) {
+            if let TransformationKind::ClearLoop = rewrite.kind {
+                next.push(Command::OOO);
+                map.push(idx);
+            }
+            idx += rewrite.len();
+        } else {
+            next.push(commands[idx]);
+            map.push(idx);
+            idx += 1;
+        }
+    }
+
+    (next, map)
+}
+
+/// Where the jump at `idx` goes, if it is one and has somewhere to go.
+fn target(program: &[Command], idx: usize) -> Option<usize> {
+    match program[idx] {
+        Command::MOO => jump::forward(program, idx),
+        Command::moo | Command::mOO => jump::backward(program, idx),
+        _ => None,
+    }
+}
+
+/// The first and last index of the commands the jump at `idx` reads to find
+/// `target`.
+fn scan(program: &[Command], idx: usize, target: Option<usize>) -> (usize, usize) {
+    if program[idx] == Command::MOO {
+        (idx, target.unwrap_or(program.len() - 1))
+    } else {
+        (target.unwrap_or(0), idx)
+    }
+}
+
+/// Whether `rewrite` removes a jump or leaves one next to the gap it makes in
+/// `after`. Other rewrites can't move a jump, since matching only depends on
+/// the order of the jumps and the commands right next to them.
+fn disturbs(rewrite: &Rewrite, after: &[Command], map: &[usize]) -> bool {
+    if !matches!(
+        rewrite.kind,
+        TransformationKind::CancelledIncrement | TransformationKind::CancelledMove
+    ) {
+        return true;
+    }
+
+    let gap = map.partition_point(|&idx| idx < rewrite.idx);
+    [gap.checked_sub(1), Some(gap)]
+        .into_iter()
+        .flatten()
+        .filter_map(|idx| after.get(idx))
+        .any(|command| matches!(command, Command::MOO | Command::moo | Command::mOO))
+}
+
+/// A jump that goes somewhere else after a rewrite, with indices from before
+/// it.
+struct Moved {
+    idx: usize,
+    before: Option<usize>,
+    after: Option<usize>,
+}
+
+impl Moved {
+    /// The commands a rewrite must have touched to move the jump. If `narrow`,
+    /// these are the ones between where it went and where it goes now, which
+    /// is usually enough. Otherwise they are all those it scanned over either
+    /// time, which always is, since a scan over the same commands ends in the
+    /// same place.
+    fn range(&self, program: &[Command], narrow: bool) -> (usize, usize) {
+        let (start, end) = scan(program, self.idx, self.before);
+        let (after_start, after_end) = scan(program, self.idx, self.after);
+
+        match (narrow, program[self.idx]) {
+            (true, Command::MOO) => (end.min(after_end), end.max(after_end)),
+            (true, _) => (start.min(after_start), start.max(after_start)),
+            (false, _) => (start.min(after_start), end.max(after_end)),
+        }
+    }
+}
+
+/// Finds the jumps that go somewhere else after a rewrite, given where each
+/// went before.
+fn moved(targets: &[Option<usize>], after: &[Command], map: &[usize]) -> Vec<Moved> {
+    map.iter()
+        .enumerate()
+        .filter(|&(after_idx, _)| {
+            matches!(after[after_idx], Command::MOO | Command::moo | Command::mOO)
+        })
+        .map(|(after_idx, &idx)| Moved {
+            idx,
+            before: targets[idx],
+            after: target(after, after_idx).map(|target| map[target]),
+        })
+        .filter(|jump| jump.before != jump.after)
+        .collect()
+}
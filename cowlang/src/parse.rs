@@ -0,0 +1,233 @@
+//! Parses COW source one command at a time.
+//!
+//! Words are separated by whitespace, and any word that isn't a command is a
+//! comment. [`commands`] walks borrowed text and [`read`] pulls text from a
+//! [`BufRead`] as it goes, so neither keeps more than the current word.
+
+use crate::Command;
+use std::{
+    io::{self, BufRead},
+    iter::FusedIterator,
+    str::Chars,
+};
+
+/// Where a command was written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// The byte offset of the command in the text.
+    pub offset: usize,
+    pub line: usize,
+    /// The column, counted in characters.
+    pub column: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Spanned {
+    pub command: Command,
+    pub span: Span,
+}
+
+pub fn commands(text: &str) -> Commands<'_> {
+    Commands {
+        chars: text.chars(),
+        scanner: Scanner::new(),
+    }
+}
+
+pub fn read<R: BufRead>(reader: R) -> Stream<R> {
+    Stream {
+        reader,
+        scanner: Scanner::new(),
+        pending: [0; 4],
+        pending_len: 0,
+        failed: false,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Commands<'a> {
+    chars: Chars<'a>,
+    scanner: Scanner,
+}
+
+impl Iterator for Commands<'_> {
+    type Item = Spanned;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for char in self.chars.by_ref() {
+            if let Some(spanned) = self.scanner.push(char) {
+                return Some(spanned);
+            }
+        }
+
+        self.scanner.finish()
+    }
+}
+
+impl FusedIterator for Commands<'_> {}
+
+/// The commands read from a [`BufRead`], which must hold UTF-8 text. Iteration
+/// stops after the first error.
+#[derive(Debug)]
+pub struct Stream<R> {
+    reader: R,
+    scanner: Scanner,
+    /// The bytes of a character split across reads.
+    pending: [u8; 4],
+    pending_len: usize,
+    failed: bool,
+}
+
+impl<R> Stream<R> {
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for Stream<R> {
+    type Item = io::Result<Spanned>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        loop {
+            let buf = match self.reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                }
+            };
+
+            if buf.is_empty() {
+                if self.pending_len > 0 {
+                    self.failed = true;
+                    return Some(Err(invalid_utf8()));
+                }
+                return self.scanner.finish().map(Ok);
+            }
+
+            let mut consumed = 0;
+            let mut found = None;
+
+            while consumed < buf.len() && found.is_none() {
+                let byte = buf[consumed];
+                consumed += 1;
+
+                if self.pending_len == 0 && byte.is_ascii() {
+                    found = self.scanner.push(char::from(byte));
+                    continue;
+                }
+
+                self.pending[self.pending_len] = byte;
+                self.pending_len += 1;
+
+                match std::str::from_utf8(&self.pending[..self.pending_len]) {
+                    Ok(char) => {
+                        self.pending_len = 0;
+                        found = self.scanner.push(char.chars().next().unwrap());
+                    }
+                    // the rest of the character is still to come
+                    Err(error) if error.error_len().is_none() => {}
+                    Err(_) => {
+                        self.reader.consume(consumed);
+                        self.failed = true;
+                        return Some(Err(invalid_utf8()));
+                    }
+                }
+            }
+
+            self.reader.consume(consumed);
+            if let Some(spanned) = found {
+                return Some(Ok(spanned));
+            }
+        }
+    }
+}
+
+fn invalid_utf8() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "stream did not contain valid UTF-8",
+    )
+}
+
+#[derive(Debug, Clone)]
+struct Scanner {
+    offset: usize,
+    line: usize,
+    column: usize,
+    word: Option<Word>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Word {
+    span: Span,
+    /// The word so far, while it could still be a command.
+    bytes: [u8; 3],
+    /// The number of characters so far, or 4 once it can't be a command.
+    len: usize,
+}
+
+impl Scanner {
+    fn new() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            column: 1,
+            word: None,
+        }
+    }
+
+    /// Moves past `char`, returning the command it ends, if any.
+    fn push(&mut self, char: char) -> Option<Spanned> {
+        let span = Span {
+            offset: self.offset,
+            line: self.line,
+            column: self.column,
+        };
+
+        self.offset += char.len_utf8();
+        if char == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        if char.is_whitespace() {
+            return self.finish();
+        }
+
+        let word = self.word.get_or_insert(Word {
+            span,
+            bytes: [0; 3],
+            len: 0,
+        });
+        if word.len < 3 && char.is_ascii() {
+            word.bytes[word.len] = char as u8;
+            word.len += 1;
+        } else {
+            word.len = 4;
+        }
+
+        None
+    }
+
+    /// Ends the current word, returning it if it's a command.
+    fn finish(&mut self) -> Option<Spanned> {
+        let word = self.word.take()?;
+        if word.len != 3 {
+            return None;
+        }
+
+        let command = std::str::from_utf8(&word.bytes).ok()?.parse().ok()?;
+        Some(Spanned {
+            command,
+            span: word.span,
+        })
+    }
+}
@@ -0,0 +1,765 @@
+//! A preprocessor that expands includes and macros into a program.
+//!
+//! ```text
+//! #include "lib/char.cow"
+//!
+//! #define clear MOO MOo moo
+//! #define print(n)
+//!     MoO*$n Moo @clear
+//! #end
+//!
+//! @print(72) @print(105) (@newline)*2
+//! ```
+//!
+//! Lines starting with `#` are directives. `#include "path"` expands another
+//! file in place, found by a [`Loader`]. A file that was already included is
+//! skipped, so a library can be included by every file that needs it.
+//! `#define name` starts a macro, which may take parameters written directly
+//! after the name, as in `name(a, b)`. Its body is the rest of the line, or if
+//! that is empty, the lines up to `#end`.
+//!
+//! `@name` expands a macro, with any arguments in parentheses, and `$name` in
+//! a body stands for an argument. A command, an expansion or a parenthesized
+//! group followed by `*n` is repeated `n` times. Other words are left out as
+//! comments, but `(`, `)`, `,` and `*` are read wherever they appear, so a
+//! plain COW file whose comments use them may mean something else once
+//! preprocessed.
+//!
+//! Every command, group and expansion counts as an item each time it is
+//! repeated, and a program may expand to at most [`MAX_ITEMS`] of them. Groups
+//! and expansions may nest at most [`MAX_DEPTH`] deep.
+//!
+//! The [`SourceMap`] records where every command was written, and through
+//! which expansions, so errors in the program can point back at the source.
+
+use crate::{Command, Program};
+use std::{
+    collections::HashMap,
+    fmt, io,
+    path::{Component, Path, PathBuf},
+};
+
+/// The most items a program may expand to, counting repetitions.
+pub const MAX_ITEMS: usize = 1 << 24;
+
+/// The deepest groups and expansions may nest inside each other.
+pub const MAX_DEPTH: usize = 256;
+
+/// Finds the files named by `#include`.
+pub trait Loader {
+    /// Loads `path` as written in the file named `from`, returning the name
+    /// of the file found and its source.
+    fn load(&mut self, from: &str, path: &str) -> io::Result<(String, String)>;
+}
+
+/// Loads files from disk, relative to the directory of the including file.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct FileLoader;
+
+impl Loader for FileLoader {
+    fn load(&mut self, from: &str, path: &str) -> io::Result<(String, String)> {
+        let path = normalize(&Path::new(from).parent().unwrap_or(Path::new("")).join(path));
+        let source = std::fs::read_to_string(&path)?;
+        Ok((path.display().to_string(), source))
+    }
+}
+
+/// Loads files by name from memory.
+impl Loader for HashMap<String, String> {
+    fn load(&mut self, _from: &str, path: &str) -> io::Result<(String, String)> {
+        match self.get(path) {
+            Some(source) => Ok((path.to_string(), source.clone())),
+            None => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+}
+
+/// Removes `.` and `..` components where possible, so a file has one name
+/// however it is reached.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+#[derive(Debug, Clone)]
+pub struct Preprocessed {
+    pub program: Program<'static>,
+    pub source_map: SourceMap,
+}
+
+/// Where each command of a preprocessed program came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// The names of the files read, with the root file first.
+    pub files: Vec<String>,
+    /// The origin of each command, by index.
+    pub origins: Vec<Origin>,
+}
+
+impl SourceMap {
+    pub fn origin(&self, idx: usize) -> Option<&Origin> {
+        self.origins.get(idx)
+    }
+
+    /// Formats `location` as `file:line:column`.
+    pub fn describe(&self, location: Location) -> String {
+        format!(
+            "{}:{}:{}",
+            self.files[location.file], location.line, location.column
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// The index of the file in [`SourceMap::files`].
+    pub file: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    /// Where the command is written, which is in a macro's body if it came
+    /// from one.
+    pub location: Location,
+    /// The expansions the command came from, outermost first.
+    pub expansions: Vec<Expansion>,
+}
+
+impl Origin {
+    /// Where the command appears in a file's own code, outside any macro.
+    pub fn root(&self) -> Location {
+        self.expansions
+            .first()
+            .map_or(self.location, |expansion| expansion.location)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expansion {
+    pub name: String,
+    pub location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    UnknownDirective(String),
+    ExpectedPath,
+    Include {
+        path: String,
+        message: String,
+    },
+    IncludeCycle(String),
+    ExpectedMacroName,
+    ExpectedParameter,
+    DuplicateMacro(String),
+    DuplicateParameter(String),
+    DirectiveInMacro,
+    UnclosedMacro(String),
+    UnexpectedEnd,
+    UndefinedMacro(String),
+    UnknownParameter(String),
+    WrongArgumentCount {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    RecursiveMacro(String),
+    InvalidCount(String),
+    UnclosedParen,
+    /// The program expands to more than [`MAX_ITEMS`] items.
+    TooManyItems,
+    /// Groups and expansions nest more than [`MAX_DEPTH`] deep.
+    TooDeep,
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}: ", self.file, self.line, self.column)?;
+
+        match &self.kind {
+            ErrorKind::UnknownDirective(name) => write!(f, "unknown directive `{name}`"),
+            ErrorKind::ExpectedPath => f.write_str("expected a quoted path"),
+            ErrorKind::Include { path, message } => {
+                write!(f, "failed to include `{path}`: {message}")
+            }
+            ErrorKind::IncludeCycle(name) => write!(f, "`{name}` includes itself"),
+            ErrorKind::ExpectedMacroName => f.write_str("expected a macro name"),
+            ErrorKind::ExpectedParameter => f.write_str("expected a parameter name"),
+            ErrorKind::DuplicateMacro(name) => write!(f, "macro `{name}` is already defined"),
+            ErrorKind::DuplicateParameter(name) => {
+                write!(f, "parameter `{name}` is already declared")
+            }
+            ErrorKind::DirectiveInMacro => f.write_str("directives cannot be used in a macro"),
+            ErrorKind::UnclosedMacro(name) => write!(f, "macro `{name}` has no `#end`"),
+            ErrorKind::UnexpectedEnd => f.write_str("`#end` outside a macro"),
+            ErrorKind::UndefinedMacro(name) => write!(f, "undefined macro `{name}`"),
+            ErrorKind::UnknownParameter(name) => write!(f, "unknown parameter `{name}`"),
+            ErrorKind::WrongArgumentCount {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "macro `{name}` takes {expected} arguments, but {found} were given"
+            ),
+            ErrorKind::RecursiveMacro(name) => write!(f, "macro `{name}` expands itself"),
+            ErrorKind::InvalidCount(found) => write!(f, "expected a count, found `{found}`"),
+            ErrorKind::UnclosedParen => f.write_str("unclosed `(`"),
+            ErrorKind::TooManyItems => {
+                write!(f, "program expands to more than {MAX_ITEMS} items")
+            }
+            ErrorKind::TooDeep => {
+                write!(f, "groups and expansions nest more than {MAX_DEPTH} deep")
+            }
+        }
+    }
+}
+
+/// Preprocesses `source`, the contents of the file `name`.
+pub fn preprocess(
+    name: &str,
+    source: &str,
+    loader: &mut impl Loader,
+) -> Result<Preprocessed, Error> {
+    let mut preprocessor = Preprocessor {
+        loader,
+        files: Vec::new(),
+        including: Vec::new(),
+        macros: HashMap::new(),
+        commands: Vec::new(),
+        origins: Vec::new(),
+        items: 0,
+        depth: 0,
+    };
+    preprocessor.file(name.to_string(), source)?;
+
+    Ok(Preprocessed {
+        program: Program::new(preprocessor.commands),
+        source_map: SourceMap {
+            files: preprocessor.files,
+            origins: preprocessor.origins,
+        },
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Word(String),
+    Open,
+    Close,
+    Comma,
+    Star,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    location: Location,
+    /// Whether the token directly follows the previous one, with no space.
+    attached: bool,
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match &self.kind {
+            TokenKind::Word(word) => word,
+            TokenKind::Open => "(",
+            TokenKind::Close => ")",
+            TokenKind::Comma => ",",
+            TokenKind::Star => "*",
+        }
+    }
+}
+
+fn tokenize(text: &str, file: usize, line: usize) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word: Option<(String, Location, bool)> = None;
+    let mut attached = false;
+
+    for (column, char) in text.chars().enumerate() {
+        let location = Location {
+            file,
+            line,
+            column: column + 1,
+        };
+        let punctuation = match char {
+            '(' => Some(TokenKind::Open),
+            ')' => Some(TokenKind::Close),
+            ',' => Some(TokenKind::Comma),
+            '*' => Some(TokenKind::Star),
+            _ => None,
+        };
+
+        if (char.is_whitespace() || punctuation.is_some())
+            && let Some((word, location, attached)) = word.take()
+        {
+            tokens.push(Token {
+                kind: TokenKind::Word(word),
+                location,
+                attached,
+            });
+        }
+
+        if let Some(kind) = punctuation {
+            tokens.push(Token {
+                kind,
+                location,
+                attached,
+            });
+        } else if !char.is_whitespace() {
+            word.get_or_insert_with(|| (String::new(), location, attached))
+                .0
+                .push(char);
+        }
+
+        attached = !char.is_whitespace();
+    }
+
+    if let Some((word, location, attached)) = word {
+        tokens.push(Token {
+            kind: TokenKind::Word(word),
+            location,
+            attached,
+        });
+    }
+
+    tokens
+}
+
+/// The index of the `)` closing the `(` at `open`.
+fn closing(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+
+    for (idx, token) in tokens.iter().enumerate().skip(open) {
+        match token.kind {
+            TokenKind::Open => depth += 1,
+            TokenKind::Close if depth == 1 => return Some(idx),
+            TokenKind::Close => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn is_name(word: &str) -> bool {
+    !word.is_empty()
+        && word
+            .chars()
+            .all(|char| char.is_alphanumeric() || char == '_')
+}
+
+#[derive(Debug, Clone)]
+struct Macro {
+    params: Vec<String>,
+    body: Vec<Token>,
+}
+
+struct Definition {
+    name: String,
+    location: Location,
+    value: Macro,
+}
+
+enum Item<'t> {
+    Command(Command, Location),
+    Expansion {
+        name: &'t str,
+        args: Vec<&'t [Token]>,
+        location: Location,
+    },
+    Group(&'t [Token], Location),
+}
+
+struct Preprocessor<'l, L> {
+    loader: &'l mut L,
+    files: Vec<String>,
+    /// The files being preprocessed, each including the next.
+    including: Vec<usize>,
+    macros: HashMap<String, Macro>,
+    commands: Vec<Command>,
+    origins: Vec<Origin>,
+    /// The number of items expanded so far.
+    items: usize,
+    /// The number of groups and expansions being expanded.
+    depth: usize,
+}
+
+impl<L: Loader> Preprocessor<'_, L> {
+    fn error(&self, location: Location, kind: ErrorKind) -> Error {
+        Error {
+            file: self.files[location.file].clone(),
+            line: location.line,
+            column: location.column,
+            kind,
+        }
+    }
+
+    fn file(&mut self, name: String, source: &str) -> Result<(), Error> {
+        let file = self.files.len();
+        self.files.push(name);
+        self.including.push(file);
+
+        // code is expanded up to each directive, so macros must be defined
+        // before the code that uses them
+        let mut code = Vec::new();
+        let mut definition = None::<Definition>;
+
+        for (line_idx, line) in source.lines().enumerate() {
+            let tokens = tokenize(line, file, line_idx + 1);
+
+            let Some(directive) = tokens
+                .first()
+                .filter(|_| line.trim_start().starts_with('#'))
+            else {
+                match &mut definition {
+                    Some(definition) => definition.value.body.extend(tokens),
+                    None => code.extend(tokens),
+                }
+                continue;
+            };
+
+            let location = directive.location;
+            match (directive.text(), definition.is_some()) {
+                ("#end", true) => {
+                    let Definition { name, value, .. } = definition.take().unwrap();
+                    self.macros.insert(name, value);
+                }
+                ("#end", false) => return Err(self.error(location, ErrorKind::UnexpectedEnd)),
+                (_, true) => return Err(self.error(location, ErrorKind::DirectiveInMacro)),
+                ("#include", false) => {
+                    self.expand(&code, &mut Vec::new())?;
+                    code.clear();
+                    self.include(line, &tokens)?;
+                }
+                ("#define", false) => {
+                    let (name, value) = self.define(&tokens)?;
+                    if value.body.is_empty() {
+                        definition = Some(Definition {
+                            name,
+                            location,
+                            value,
+                        });
+                    } else {
+                        self.macros.insert(name, value);
+                    }
+                }
+                (directive, false) => {
+                    let kind = ErrorKind::UnknownDirective(directive.to_string());
+                    return Err(self.error(location, kind));
+                }
+            }
+        }
+
+        if let Some(definition) = definition {
+            let kind = ErrorKind::UnclosedMacro(definition.name);
+            return Err(self.error(definition.location, kind));
+        }
+
+        self.expand(&code, &mut Vec::new())?;
+        self.including.pop();
+        Ok(())
+    }
+
+    fn include(&mut self, line: &str, tokens: &[Token]) -> Result<(), Error> {
+        let location = tokens
+            .get(1)
+            .map_or(tokens[0].location, |token| token.location);
+        let path = line
+            .trim()
+            .strip_prefix("#include")
+            .map(str::trim)
+            .and_then(|path| path.strip_prefix('"')?.strip_suffix('"'))
+            .ok_or_else(|| self.error(location, ErrorKind::ExpectedPath))?;
+
+        let from = &self.files[location.file];
+        let (name, source) = self.loader.load(from, path).map_err(|error| {
+            let kind = ErrorKind::Include {
+                path: path.to_string(),
+                message: error.to_string(),
+            };
+            self.error(location, kind)
+        })?;
+
+        match self.files.iter().position(|file| *file == name) {
+            Some(file) if self.including.contains(&file) => {
+                Err(self.error(location, ErrorKind::IncludeCycle(name)))
+            }
+            Some(_) => Ok(()),
+            None => self.file(name, &source),
+        }
+    }
+
+    fn define(&mut self, tokens: &[Token]) -> Result<(String, Macro), Error> {
+        let location = tokens[0].location;
+        let name = match tokens.get(1) {
+            Some(Token {
+                kind: TokenKind::Word(name),
+                ..
+            }) if is_name(name) => name.clone(),
+            Some(token) => return Err(self.error(token.location, ErrorKind::ExpectedMacroName)),
+            None => return Err(self.error(location, ErrorKind::ExpectedMacroName)),
+        };
+        if self.macros.contains_key(&name) {
+            return Err(self.error(tokens[1].location, ErrorKind::DuplicateMacro(name)));
+        }
+
+        let mut params = Vec::new();
+        let mut body = &tokens[2..];
+
+        if let Some(open) = body
+            .first()
+            .filter(|token| token.kind == TokenKind::Open && token.attached)
+        {
+            let close = closing(body, 0)
+                .ok_or_else(|| self.error(open.location, ErrorKind::UnclosedParen))?;
+
+            for (idx, token) in body[1..close].iter().enumerate() {
+                let expected = if idx % 2 == 0 { "word" } else { "," };
+                match &token.kind {
+                    TokenKind::Word(param) if expected == "word" && is_name(param) => {
+                        if params.contains(param) {
+                            let kind = ErrorKind::DuplicateParameter(param.clone());
+                            return Err(self.error(token.location, kind));
+                        }
+                        params.push(param.clone());
+                    }
+                    TokenKind::Comma if expected == "," => {}
+                    _ => return Err(self.error(token.location, ErrorKind::ExpectedParameter)),
+                }
+            }
+            if close > 1 && body[close - 1].kind == TokenKind::Comma {
+                return Err(self.error(body[close].location, ErrorKind::ExpectedParameter));
+            }
+
+            body = &body[close + 1..];
+        }
+
+        Ok((
+            name,
+            Macro {
+                params,
+                body: body.to_vec(),
+            },
+        ))
+    }
+
+    /// Expands `tokens` into commands, inside the macro expansions
+    /// `expansions`.
+    fn expand(&mut self, tokens: &[Token], expansions: &mut Vec<Expansion>) -> Result<(), Error> {
+        let mut idx = 0;
+
+        while idx < tokens.len() {
+            let token = &tokens[idx];
+            idx += 1;
+
+            let item = match &token.kind {
+                TokenKind::Word(word) if word.starts_with('@') => {
+                    let name = &word[1..];
+                    if !is_name(name) {
+                        return Err(self.error(token.location, ErrorKind::ExpectedMacroName));
+                    }
+
+                    let mut args = Vec::new();
+                    if let Some(open) = tokens
+                        .get(idx)
+                        .filter(|token| token.kind == TokenKind::Open && token.attached)
+                    {
+                        let close = closing(tokens, idx)
+                            .ok_or_else(|| self.error(open.location, ErrorKind::UnclosedParen))?;
+                        args = split_args(&tokens[idx + 1..close]);
+                        idx = close + 1;
+                    }
+
+                    Item::Expansion {
+                        name,
+                        args,
+                        location: token.location,
+                    }
+                }
+                TokenKind::Word(word) if word.starts_with('$') => {
+                    let kind = ErrorKind::UnknownParameter(word[1..].to_string());
+                    return Err(self.error(token.location, kind));
+                }
+                TokenKind::Word(word) => match word.parse::<Command>() {
+                    Ok(command) => Item::Command(command, token.location),
+                    Err(()) => continue,
+                },
+                TokenKind::Open => {
+                    let close = closing(tokens, idx - 1)
+                        .ok_or_else(|| self.error(token.location, ErrorKind::UnclosedParen))?;
+                    let group = &tokens[idx..close];
+                    idx = close + 1;
+                    Item::Group(group, token.location)
+                }
+                TokenKind::Close | TokenKind::Comma | TokenKind::Star => continue,
+            };
+
+            let mut count = 1;
+            if let Some(star) = tokens
+                .get(idx)
+                .filter(|token| token.kind == TokenKind::Star)
+            {
+                count = match tokens.get(idx + 1) {
+                    Some(token) => token.text().parse::<usize>().map_err(|_| {
+                        let kind = ErrorKind::InvalidCount(token.text().to_string());
+                        self.error(token.location, kind)
+                    })?,
+                    None => {
+                        let kind = ErrorKind::InvalidCount(String::new());
+                        return Err(self.error(star.location, kind));
+                    }
+                };
+                idx += 2;
+            }
+
+            for _ in 0..count {
+                self.items += 1;
+                if self.items > MAX_ITEMS {
+                    return Err(self.error(token.location, ErrorKind::TooManyItems));
+                }
+                self.item(&item, expansions)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn item(&mut self, item: &Item, expansions: &mut Vec<Expansion>) -> Result<(), Error> {
+        match *item {
+            Item::Command(command, location) => {
+                self.commands.push(command);
+                self.origins.push(Origin {
+                    location,
+                    expansions: expansions.clone(),
+                });
+            }
+            Item::Group(tokens, location) => self.nested(tokens, location, expansions)?,
+            Item::Expansion {
+                name,
+                ref args,
+                location,
+            } => {
+                let Some(value) = self.macros.get(name).cloned() else {
+                    let kind = ErrorKind::UndefinedMacro(name.to_string());
+                    return Err(self.error(location, kind));
+                };
+                if value.params.len() != args.len() {
+                    let kind = ErrorKind::WrongArgumentCount {
+                        name: name.to_string(),
+                        expected: value.params.len(),
+                        found: args.len(),
+                    };
+                    return Err(self.error(location, kind));
+                }
+                if expansions.iter().any(|expansion| expansion.name == name) {
+                    let kind = ErrorKind::RecursiveMacro(name.to_string());
+                    return Err(self.error(location, kind));
+                }
+
+                let body = substitute(&value, args);
+                expansions.push(Expansion {
+                    name: name.to_string(),
+                    location,
+                });
+                self.nested(&body, location, expansions)?;
+                expansions.pop();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Expands the tokens of a group or macro body written at `location`.
+    fn nested(
+        &mut self,
+        tokens: &[Token],
+        location: Location,
+        expansions: &mut Vec<Expansion>,
+    ) -> Result<(), Error> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(location, ErrorKind::TooDeep));
+        }
+
+        self.depth += 1;
+        let result = self.expand(tokens, expansions);
+        self.depth -= 1;
+        result
+    }
+}
+
+/// Splits the tokens between a call's parentheses at the commas outside any
+/// nested parentheses.
+fn split_args(tokens: &[Token]) -> Vec<&[Token]> {
+    if tokens.is_empty() {
+        return Vec::new();
+    }
+
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (idx, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::Open => depth += 1,
+            TokenKind::Close => depth -= 1,
+            TokenKind::Comma if depth == 0 => {
+                args.push(&tokens[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+
+    args.push(&tokens[start..]);
+    args
+}
+
+/// The body of `definition` with each `$param` replaced by its argument.
+fn substitute(definition: &Macro, args: &[&[Token]]) -> Vec<Token> {
+    let mut body = Vec::with_capacity(definition.body.len());
+
+    for token in &definition.body {
+        let arg = match &token.kind {
+            TokenKind::Word(word) => word
+                .strip_prefix('$')
+                .and_then(|param| definition.params.iter().position(|p| p == param)),
+            _ => None,
+        };
+
+        match arg {
+            Some(arg) => body.extend(args[arg].iter().cloned()),
+            None => body.push(token.clone()),
+        }
+    }
+
+    body
+}
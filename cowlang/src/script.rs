@@ -0,0 +1,902 @@
+//! A small structured language that compiles to COW.
+//!
+//! ```text
+//! // prints the first ten squares
+//! let i = 1;
+//! while i <= 10 {
+//!     print i * i;
+//!     print "\n";
+//!     i = i + 1;
+//! }
+//! ```
+//!
+//! Statements are `let name = expr;`, `name = expr;`, `if expr { … }` with an
+//! optional `else { … }` or `else if`, `while expr { … }`, `print "text";`,
+//! `print expr;` and `read name;`, and `//` starts a comment. Expressions
+//! combine integers and variables with `+ - * / %`, the comparisons
+//! `== != < <= > >=`, and `&&`, `||` and `!`, where 0 is false.
+//!
+//! Values are cells, so they are unsigned and saturate: `2 - 3` is 0.
+//! Dividing by 0 gives 0, and the remainder is the dividend. Variables live
+//! for the whole program, but each must be declared with `let` before it is
+//! used.
+//!
+//! Every variable gets its own cell at the start of the tape, and the cells
+//! after them are used as a stack for temporaries. Copies go through the
+//! register, which is empty between statements.
+
+use crate::{Builder, Command, Program};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+pub fn compile(source: &str) -> Result<Program<'static>, Error> {
+    let tokens = lex(source)?;
+    let statements = Parser {
+        tokens: &tokens,
+        idx: 0,
+    }
+    .program()?;
+
+    let mut vars = HashMap::new();
+    allocate(&statements, &mut vars)?;
+
+    let mut generator = Generator {
+        builder: Builder::new(),
+        position: 0,
+        next_temp: vars.len(),
+        vars,
+        declared: HashSet::new(),
+    };
+    generator.block(&statements)?;
+
+    Ok(generator.builder.build())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    UnexpectedChar(char),
+    UnterminatedString,
+    InvalidEscape(char),
+    NulInString,
+    IntegerTooLarge(String),
+    Expected { expected: String, found: String },
+    UndefinedVariable(String),
+    DuplicateVariable(String),
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+
+        match &self.kind {
+            ErrorKind::UnexpectedChar(char) => write!(f, "unexpected character `{char}`"),
+            ErrorKind::UnterminatedString => f.write_str("unterminated string"),
+            ErrorKind::InvalidEscape(char) => write!(f, "invalid escape `\\{char}`"),
+            ErrorKind::NulInString => f.write_str("strings cannot contain NUL"),
+            ErrorKind::IntegerTooLarge(digits) => write!(f, "integer `{digits}` is too large"),
+            ErrorKind::Expected { expected, found } => {
+                write!(f, "expected {expected}, found {found}")
+            }
+            ErrorKind::UndefinedVariable(name) => write!(f, "undefined variable `{name}`"),
+            ErrorKind::DuplicateVariable(name) => {
+                write!(f, "variable `{name}` is already declared")
+            }
+        }
+    }
+}
+
+const KEYWORDS: &[&str] = &["let", "if", "else", "while", "print", "read"];
+
+const SYMBOLS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "(", ")", "{", "}", ";", "=", "<", ">", "+", "-", "*", "/",
+    "%", "!",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Ident(String),
+    Int(u32),
+    Str(String),
+    Symbol(&'static str),
+    Eof,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Ident(ident) => write!(f, "`{ident}`"),
+            TokenKind::Int(int) => write!(f, "`{int}`"),
+            TokenKind::Str(_) => f.write_str("a string"),
+            TokenKind::Symbol(symbol) => write!(f, "`{symbol}`"),
+            TokenKind::Eof => f.write_str("end of input"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+fn lex(source: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut line_start = 0;
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(idx, char)) = chars.peek() {
+        let column = source[line_start..idx].chars().count() + 1;
+        let error = |kind| Error { line, column, kind };
+        let rest = &source[idx..];
+
+        if char == '\n' {
+            chars.next();
+            line += 1;
+            line_start = idx + 1;
+            continue;
+        }
+
+        if char.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        if rest.starts_with("//") {
+            while chars.next_if(|&(_, char)| char != '\n').is_some() {}
+            continue;
+        }
+
+        let kind = if char.is_ascii_digit() {
+            let len = rest
+                .find(|char: char| !char.is_ascii_alphanumeric() && char != '_')
+                .unwrap_or(rest.len());
+            let digits = &rest[..len];
+            chars.nth(digits.chars().count() - 1);
+
+            match digits.parse() {
+                Ok(int) => TokenKind::Int(int),
+                Err(_) if digits.bytes().all(|byte| byte.is_ascii_digit()) => {
+                    return Err(error(ErrorKind::IntegerTooLarge(digits.to_string())));
+                }
+                Err(_) => {
+                    let found = format!("`{digits}`");
+                    let kind = ErrorKind::Expected {
+                        expected: "an integer".to_string(),
+                        found,
+                    };
+                    return Err(error(kind));
+                }
+            }
+        } else if char.is_alphabetic() || char == '_' {
+            let len = rest
+                .find(|char: char| !char.is_alphanumeric() && char != '_')
+                .unwrap_or(rest.len());
+            let ident = &rest[..len];
+            chars.nth(ident.chars().count() - 1);
+            TokenKind::Ident(ident.to_string())
+        } else if char == '"' {
+            chars.next();
+            let mut string = String::new();
+
+            loop {
+                match chars.next() {
+                    None | Some((_, '\n')) => {
+                        return Err(error(ErrorKind::UnterminatedString));
+                    }
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => string.push('\n'),
+                        Some((_, 't')) => string.push('\t'),
+                        Some((_, '\\')) => string.push('\\'),
+                        Some((_, '"')) => string.push('"'),
+                        Some((_, char)) => return Err(error(ErrorKind::InvalidEscape(char))),
+                        None => return Err(error(ErrorKind::UnterminatedString)),
+                    },
+                    Some((_, '\0')) => return Err(error(ErrorKind::NulInString)),
+                    Some((_, char)) => string.push(char),
+                }
+            }
+
+            TokenKind::Str(string)
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            chars.nth(symbol.len() - 1);
+            TokenKind::Symbol(symbol)
+        } else {
+            return Err(error(ErrorKind::UnexpectedChar(char)));
+        };
+
+        tokens.push(Token { kind, line, column });
+    }
+
+    let column = source[line_start..].chars().count() + 1;
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        line,
+        column,
+    });
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+struct Name {
+    text: String,
+    line: usize,
+    column: usize,
+}
+
+impl Name {
+    fn error(&self, kind: ErrorKind) -> Error {
+        Error {
+            line: self.line,
+            column: self.column,
+            kind,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Statement {
+    Let(Name, Expr),
+    Assign(Name, Expr),
+    If(Expr, Vec<Statement>, Vec<Statement>),
+    While(Expr, Vec<Statement>),
+    PrintStr(String),
+    Print(Expr),
+    Read(Name),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Int(u32),
+    Var(Name),
+    Not(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Copy, Clone)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+struct Parser<'t> {
+    tokens: &'t [Token],
+    idx: usize,
+}
+
+impl Parser<'_> {
+    fn program(&mut self) -> Result<Vec<Statement>, Error> {
+        let mut statements = Vec::new();
+
+        while self.peek().kind != TokenKind::Eof {
+            statements.push(self.statement()?);
+        }
+
+        Ok(statements)
+    }
+
+    fn block(&mut self) -> Result<Vec<Statement>, Error> {
+        self.expect("{")?;
+        let mut statements = Vec::new();
+
+        while !self.eat("}") {
+            statements.push(self.statement()?);
+        }
+
+        Ok(statements)
+    }
+
+    fn statement(&mut self) -> Result<Statement, Error> {
+        let token = self.next();
+
+        let statement = match &token.kind {
+            TokenKind::Ident(keyword) if keyword == "let" => {
+                let name = self.name()?;
+                self.expect("=")?;
+                Statement::Let(name, self.expr()?)
+            }
+            TokenKind::Ident(keyword) if keyword == "if" => return self.if_(),
+            TokenKind::Ident(keyword) if keyword == "while" => {
+                let condition = self.expr()?;
+                return Ok(Statement::While(condition, self.block()?));
+            }
+            TokenKind::Ident(keyword) if keyword == "print" => {
+                if let TokenKind::Str(string) = &self.peek().kind {
+                    let string = string.clone();
+                    self.next();
+                    Statement::PrintStr(string)
+                } else {
+                    Statement::Print(self.expr()?)
+                }
+            }
+            TokenKind::Ident(keyword) if keyword == "read" => Statement::Read(self.name()?),
+            TokenKind::Ident(ident) if !KEYWORDS.contains(&ident.as_str()) => {
+                let name = Name {
+                    text: ident.clone(),
+                    line: token.line,
+                    column: token.column,
+                };
+                self.expect("=")?;
+                Statement::Assign(name, self.expr()?)
+            }
+            _ => return Err(expected(&token, "a statement")),
+        };
+
+        self.expect(";")?;
+        Ok(statement)
+    }
+
+    fn if_(&mut self) -> Result<Statement, Error> {
+        let condition = self.expr()?;
+        let then = self.block()?;
+
+        let otherwise = if self.eat_keyword("else") {
+            if self.eat_keyword("if") {
+                vec![self.if_()?]
+            } else {
+                self.block()?
+            }
+        } else {
+            Vec::new()
+        };
+
+        Ok(Statement::If(condition, then, otherwise))
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        self.binary(0)
+    }
+
+    /// Parses operators from the given precedence level upward.
+    fn binary(&mut self, level: usize) -> Result<Expr, Error> {
+        const LEVELS: &[&[(&str, BinOp)]] = &[
+            &[("||", BinOp::Or)],
+            &[("&&", BinOp::And)],
+            &[
+                ("==", BinOp::Eq),
+                ("!=", BinOp::Ne),
+                ("<=", BinOp::Le),
+                (">=", BinOp::Ge),
+                ("<", BinOp::Lt),
+                (">", BinOp::Gt),
+            ],
+            &[("+", BinOp::Add), ("-", BinOp::Sub)],
+            &[("*", BinOp::Mul), ("/", BinOp::Div), ("%", BinOp::Rem)],
+        ];
+
+        let Some(operators) = LEVELS.get(level) else {
+            return self.unary();
+        };
+
+        let mut lhs = self.binary(level + 1)?;
+
+        'operators: loop {
+            for &(symbol, op) in *operators {
+                if self.eat(symbol) {
+                    let rhs = self.binary(level + 1)?;
+                    lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+                    continue 'operators;
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, Error> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+
+        if self.eat("(") {
+            let expr = self.expr()?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+
+        let token = self.peek().clone();
+        match token.kind {
+            TokenKind::Int(int) => {
+                self.next();
+                Ok(Expr::Int(int))
+            }
+            TokenKind::Ident(_) => Ok(Expr::Var(self.name()?)),
+            _ => Err(expected(&token, "an expression")),
+        }
+    }
+
+    fn name(&mut self) -> Result<Name, Error> {
+        let token = self.next();
+
+        match &token.kind {
+            TokenKind::Ident(ident) if !KEYWORDS.contains(&ident.as_str()) => Ok(Name {
+                text: ident.clone(),
+                line: token.line,
+                column: token.column,
+            }),
+            _ => Err(expected(&token, "a variable name")),
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), Error> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(expected(self.peek(), &format!("`{symbol}`")))
+        }
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        let matches = matches!(self.peek().kind, TokenKind::Symbol(found) if found == symbol);
+        if matches {
+            self.next();
+        }
+        matches
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let matches = matches!(&self.peek().kind, TokenKind::Ident(ident) if ident == keyword);
+        if matches {
+            self.next();
+        }
+        matches
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.idx]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.idx].clone();
+        self.idx = (self.idx + 1).min(self.tokens.len() - 1);
+        token
+    }
+}
+
+fn expected(token: &Token, expected: &str) -> Error {
+    Error {
+        line: token.line,
+        column: token.column,
+        kind: ErrorKind::Expected {
+            expected: expected.to_string(),
+            found: token.kind.to_string(),
+        },
+    }
+}
+
+/// Gives every declared variable a cell, in the order they are declared.
+fn allocate(statements: &[Statement], vars: &mut HashMap<String, usize>) -> Result<(), Error> {
+    for statement in statements {
+        match statement {
+            Statement::Let(name, _) => {
+                if vars.contains_key(&name.text) {
+                    let kind = ErrorKind::DuplicateVariable(name.text.clone());
+                    return Err(name.error(kind));
+                }
+                vars.insert(name.text.clone(), vars.len());
+            }
+            Statement::If(_, then, otherwise) => {
+                allocate(then, vars)?;
+                allocate(otherwise, vars)?;
+            }
+            Statement::While(_, body) => allocate(body, vars)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+struct Generator {
+    builder: Builder,
+    position: usize,
+    vars: HashMap<String, usize>,
+    /// The variables whose `let` has been compiled, which may be used.
+    declared: HashSet<String>,
+    next_temp: usize,
+}
+
+impl Generator {
+    fn block(&mut self, statements: &[Statement]) -> Result<(), Error> {
+        for statement in statements {
+            self.statement(statement)?;
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<(), Error> {
+        match statement {
+            Statement::Let(name, expr) => {
+                let temp = self.alloc();
+                self.expr(expr, temp)?;
+                self.declared.insert(name.text.clone());
+                let var = self.var(name)?;
+                self.copy(temp, var);
+                self.free(temp);
+            }
+            Statement::Assign(name, expr) => {
+                let var = self.var(name)?;
+                let temp = self.alloc();
+                self.expr(expr, temp)?;
+                self.copy(temp, var);
+                self.free(temp);
+            }
+            Statement::If(condition, then, otherwise) => {
+                let cond = self.alloc();
+                self.expr(condition, cond)?;
+
+                if otherwise.is_empty() {
+                    self.loop_(cond, |this| {
+                        this.block(then)?;
+                        this.clear(cond);
+                        Ok(())
+                    })?;
+                } else {
+                    let other = self.alloc();
+                    self.set(other, 1);
+
+                    self.loop_(cond, |this| {
+                        this.block(then)?;
+                        this.clear(other);
+                        this.clear(cond);
+                        Ok(())
+                    })?;
+                    self.loop_(other, |this| {
+                        this.block(otherwise)?;
+                        this.clear(other);
+                        Ok(())
+                    })?;
+
+                    self.free(other);
+                }
+
+                self.free(cond);
+            }
+            Statement::While(condition, body) => {
+                let cond = self.alloc();
+                self.expr(condition, cond)?;
+
+                self.loop_(cond, |this| {
+                    this.block(body)?;
+                    this.expr(condition, cond)
+                })?;
+
+                self.free(cond);
+            }
+            Statement::PrintStr(string) => {
+                let temp = self.alloc();
+                self.clear(temp);
+
+                let mut value = 0;
+                for char in string.chars() {
+                    let code = char as u32;
+
+                    if code > value {
+                        self.add(temp, code - value, Command::MoO);
+                    } else {
+                        self.add(temp, value - code, Command::MOo);
+                    }
+
+                    self.goto(temp);
+                    self.builder.push(Command::Moo);
+                    value = code;
+                }
+
+                self.free(temp);
+            }
+            Statement::Print(expr) => {
+                let temp = self.alloc();
+                self.expr(expr, temp)?;
+                self.goto(temp);
+                self.builder.push(Command::OOM);
+                self.free(temp);
+            }
+            Statement::Read(name) => {
+                let var = self.var(name)?;
+                self.goto(var);
+                self.builder.push(Command::oom);
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates `expr` into the temporary cell `dst`.
+    fn expr(&mut self, expr: &Expr, dst: usize) -> Result<(), Error> {
+        match expr {
+            Expr::Int(int) => self.set(dst, *int),
+            Expr::Var(name) => {
+                let var = self.var(name)?;
+                self.copy(var, dst);
+            }
+            Expr::Not(expr) => {
+                self.expr(expr, dst)?;
+                self.not(dst);
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                self.expr(lhs, dst)?;
+                let rhs_cell = self.alloc();
+                self.expr(rhs, rhs_cell)?;
+                self.binary(*op, dst, rhs_cell);
+                self.free(rhs_cell);
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies `op` to `lhs` and `rhs`, leaving the result in `lhs`. The
+    /// value left in `rhs` is unspecified.
+    fn binary(&mut self, op: BinOp, lhs: usize, rhs: usize) {
+        match op {
+            BinOp::Add => self.move_add(rhs, lhs, Command::MoO),
+            BinOp::Sub => self.move_add(rhs, lhs, Command::MOo),
+            BinOp::Mul => {
+                let counter = self.alloc();
+                self.copy(lhs, counter);
+                self.clear(lhs);
+
+                self.loop_unchecked(counter, |this| {
+                    this.builder.push(Command::MOo);
+                    let temp = this.alloc();
+                    this.copy(rhs, temp);
+                    this.move_add(temp, lhs, Command::MoO);
+                    this.free(temp);
+                });
+
+                self.free(counter);
+            }
+            BinOp::Div | BinOp::Rem => {
+                let remainder = self.alloc();
+                self.copy(lhs, remainder);
+                self.clear(lhs);
+
+                let cond = self.alloc();
+                self.divides(remainder, rhs, cond);
+
+                self.loop_unchecked(cond, |this| {
+                    let temp = this.alloc();
+                    this.copy(rhs, temp);
+                    this.move_add(temp, remainder, Command::MOo);
+                    this.free(temp);
+
+                    this.goto(lhs);
+                    this.builder.push(Command::MoO);
+
+                    this.divides(remainder, rhs, cond);
+                });
+
+                self.free(cond);
+
+                if let BinOp::Rem = op {
+                    self.copy(remainder, lhs);
+                }
+                self.free(remainder);
+            }
+            BinOp::Eq | BinOp::Ne => {
+                let rhs_minus_lhs = self.alloc();
+                self.copy(rhs, rhs_minus_lhs);
+
+                let temp = self.alloc();
+                self.copy(lhs, temp);
+                self.move_add(temp, rhs_minus_lhs, Command::MOo);
+                self.free(temp);
+
+                // one of the differences is 0, so their sum is the distance
+                self.move_add(rhs, lhs, Command::MOo);
+                self.move_add(rhs_minus_lhs, lhs, Command::MoO);
+                self.free(rhs_minus_lhs);
+
+                if let BinOp::Eq = op {
+                    self.not(lhs);
+                } else {
+                    self.bool(lhs);
+                }
+            }
+            BinOp::Gt | BinOp::Le => {
+                self.move_add(rhs, lhs, Command::MOo);
+
+                if let BinOp::Gt = op {
+                    self.bool(lhs);
+                } else {
+                    self.not(lhs);
+                }
+            }
+            BinOp::Lt | BinOp::Ge => {
+                self.move_add(lhs, rhs, Command::MOo);
+                self.copy(rhs, lhs);
+
+                if let BinOp::Lt = op {
+                    self.bool(lhs);
+                } else {
+                    self.not(lhs);
+                }
+            }
+            BinOp::And => {
+                self.bool(rhs);
+                let result = self.alloc();
+                self.clear(result);
+
+                self.loop_unchecked(lhs, |this| {
+                    this.clear(lhs);
+                    this.copy(rhs, result);
+                });
+
+                self.copy(result, lhs);
+                self.free(result);
+            }
+            BinOp::Or => {
+                self.move_add(rhs, lhs, Command::MoO);
+                self.bool(lhs);
+            }
+        }
+    }
+
+    /// Sets `cond` to whether `rhs` is nonzero and fits in `remainder`.
+    fn divides(&mut self, remainder: usize, rhs: usize, cond: usize) {
+        self.copy(rhs, cond);
+        let temp = self.alloc();
+        self.copy(remainder, temp);
+        self.move_add(temp, cond, Command::MOo);
+        self.not(cond);
+
+        self.copy(rhs, temp);
+        self.binary(BinOp::And, cond, temp);
+        self.free(temp);
+    }
+
+    /// Adds or subtracts the value of `src` to `dst`, leaving `src` at 0.
+    fn move_add(&mut self, src: usize, dst: usize, command: Command) {
+        self.loop_unchecked(src, |this| {
+            this.builder.push(Command::MOo);
+            this.goto(dst);
+            this.builder.push(command);
+        });
+    }
+
+    /// Turns a nonzero `cell` into 1.
+    fn bool(&mut self, cell: usize) {
+        let temp = self.alloc();
+        self.clear(temp);
+
+        self.loop_unchecked(cell, |this| {
+            this.builder.push(Command::OOO);
+            this.set(temp, 1);
+        });
+
+        self.copy(temp, cell);
+        self.free(temp);
+    }
+
+    /// Turns a nonzero `cell` into 0 and 0 into 1.
+    fn not(&mut self, cell: usize) {
+        let temp = self.alloc();
+        self.set(temp, 1);
+
+        self.loop_unchecked(cell, |this| {
+            this.builder.push(Command::OOO);
+            this.clear(temp);
+        });
+
+        self.copy(temp, cell);
+        self.free(temp);
+    }
+
+    /// Emits a loop that runs while `cell` is nonzero. The body starts and
+    /// must end with the cursor anywhere; it is moved back to `cell`.
+    fn loop_(
+        &mut self,
+        cell: usize,
+        body: impl FnOnce(&mut Self) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        self.goto(cell);
+        self.builder.push(Command::MOO);
+        body(self)?;
+        self.goto(cell);
+        self.builder.push(Command::moo);
+        Ok(())
+    }
+
+    fn loop_unchecked(&mut self, cell: usize, body: impl FnOnce(&mut Self)) {
+        self.loop_(cell, |this| {
+            body(this);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    fn set(&mut self, cell: usize, value: u32) {
+        self.clear(cell);
+        self.add(cell, value, Command::MoO);
+    }
+
+    fn clear(&mut self, cell: usize) {
+        self.goto(cell);
+        self.builder.push(Command::OOO);
+    }
+
+    /// Repeats `command` on `cell` `count` times, using a loop for large
+    /// counts.
+    fn add(&mut self, cell: usize, count: u32, command: Command) {
+        if count <= 16 {
+            self.goto(cell);
+            self.builder
+                .extend(std::iter::repeat_n(command, count as usize));
+            return;
+        }
+
+        let step = count.isqrt();
+        let temp = self.alloc();
+        self.set(temp, count / step);
+
+        self.loop_unchecked(temp, |this| {
+            this.builder.push(Command::MOo);
+            this.add(cell, step, command);
+        });
+
+        self.free(temp);
+        self.add(cell, count % step, command);
+    }
+
+    /// Copies `src` into `dst` through the register.
+    fn copy(&mut self, src: usize, dst: usize) {
+        if src == dst {
+            return;
+        }
+
+        self.goto(src);
+        self.builder.push(Command::MMM);
+        self.goto(dst);
+        self.builder.push(Command::MMM);
+    }
+
+    fn goto(&mut self, cell: usize) {
+        let command = if cell > self.position {
+            Command::moO
+        } else {
+            Command::mOo
+        };
+
+        self.builder
+            .extend(std::iter::repeat_n(command, cell.abs_diff(self.position)));
+        self.position = cell;
+    }
+
+    fn var(&self, name: &Name) -> Result<usize, Error> {
+        match self.vars.get(&name.text) {
+            Some(&cell) if self.declared.contains(&name.text) => Ok(cell),
+            _ => Err(name.error(ErrorKind::UndefinedVariable(name.text.clone()))),
+        }
+    }
+
+    fn alloc(&mut self) -> usize {
+        self.next_temp += 1;
+        self.next_temp - 1
+    }
+
+    fn free(&mut self, cell: usize) {
+        debug_assert_eq!(cell + 1, self.next_temp, "temporaries are freed in order");
+        self.next_temp -= 1;
+    }
+}
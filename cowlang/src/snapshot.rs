@@ -0,0 +1,59 @@
+use crate::{Cowlang, Dialect, Dispatches, Error, Input, InputKind, Output, Program, Value};
+
+/// The state of an interpreter apart from its input and output, which can be
+/// taken with [`Cowlang::snapshot`] and resumed with [`Cowlang::restore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot<'a> {
+    pub program: Program<'a>,
+    pub program_idx: usize,
+    pub memory: Vec<u32>,
+    pub memory_idx: usize,
+    pub register: Option<u32>,
+    pub awaiting_input: Option<InputKind>,
+    /// Input provided to a suspended command that hasn't consumed it yet.
+    pub pending_input: Option<Value>,
+    pub dispatches: Dispatches,
+    pub dialect: Dialect,
+}
+
+impl<'a, I: Input, O: Output> Cowlang<'a, I, O> {
+    /// Takes a snapshot borrowing the program.
+    pub fn snapshot(&self) -> Snapshot<'_> {
+        Snapshot {
+            program: Program::new(&*self.program),
+            program_idx: self.program_idx,
+            memory: self.memory.clone(),
+            memory_idx: self.memory_idx,
+            register: self.register,
+            awaiting_input: self.awaiting_input,
+            pending_input: self.pending_input,
+            dispatches: self.dispatches.clone(),
+            dialect: self.dialect,
+        }
+    }
+
+    /// Resumes from `snapshot` with new input and output, failing with
+    /// [`Error::InvalidSnapshot`] if an index is out of bounds.
+    pub fn restore(snapshot: Snapshot<'a>, input: I, output: O) -> Result<Self, Error> {
+        if snapshot.memory_idx >= snapshot.memory.len()
+            || snapshot.program_idx > snapshot.program.len()
+        {
+            return Err(Error::InvalidSnapshot);
+        }
+
+        Ok(Self {
+            memory: snapshot.memory,
+            memory_idx: snapshot.memory_idx,
+            program: snapshot.program,
+            program_idx: snapshot.program_idx,
+            input,
+            output,
+            register: snapshot.register,
+            awaiting_input: snapshot.awaiting_input,
+            pending_input: snapshot.pending_input,
+            dispatches: snapshot.dispatches,
+            dialect: snapshot.dialect,
+        })
+    }
+}
@@ -0,0 +1,119 @@
+use cowlang::{
+    Command, Program,
+    analysis::{Cell, Loop},
+};
+
+#[test]
+fn offsets_and_values_follow_straight_code() {
+    let analysis = Program::parse("moO moO MoO mOo oom").analyze();
+
+    let point = analysis.point(3).unwrap();
+    assert_eq!(point.offset(), Some(2));
+    assert_eq!(point.current(), Cell::Known(1));
+
+    let exit = analysis.exit().unwrap();
+    assert_eq!(exit.offset(), Some(1));
+    assert_eq!(exit.current(), Cell::Unknown);
+    assert_eq!(exit.cell(2), Cell::Known(1));
+    assert!(exit.cell(0).is_zero());
+}
+
+#[test]
+fn balanced_loops_keep_the_offset() {
+    let analysis = Program::parse("oom MOO moO MoO mOo MOo moo OOM").analyze();
+
+    assert_eq!(
+        analysis.loops(),
+        [Loop {
+            start: 1,
+            end: 6,
+            shift: Some(0),
+        }]
+    );
+    assert!(analysis.loops()[0].is_balanced());
+
+    let after = analysis.point(7).unwrap();
+    assert_eq!(after.offset(), Some(0));
+    assert!(after.current().is_zero());
+    assert_eq!(after.cell(1), Cell::Unknown);
+}
+
+#[test]
+fn moving_loops_lose_the_offset() {
+    let analysis = Program::parse("oom moO oom MOO moO moo OOM").analyze();
+
+    assert_eq!(analysis.loops()[0].shift, Some(1));
+
+    let after = analysis.point(6).unwrap();
+    assert_eq!(after.offset(), None);
+    assert_eq!(after.min_offset(), 1);
+    assert!(after.current().is_zero());
+    // the first cell was never written while the cursor was lost
+    assert_eq!(after.cell(0), Cell::Unknown);
+    assert!(analysis.is_zero(2, 1));
+}
+
+#[test]
+fn shifts_follow_the_interpreter() {
+    // `mOo` on the first cell doesn't move
+    let analysis = Program::parse("MoO MOO mOo MOo moo").analyze();
+    assert_eq!(analysis.loops()[0].shift, Some(0));
+
+    // nested loops must be balanced
+    let analysis = Program::parse("oom MOO moO oom MOO moO MOo moo mOo MOo moo").analyze();
+    assert_eq!(analysis.loops()[0].shift, None);
+    assert_eq!(analysis.loops()[1].shift, Some(1));
+
+    // what `mOO` evaluates isn't known
+    let analysis = Program::parse("oom MOO mOO moo").analyze();
+    assert_eq!(analysis.loops()[0].shift, None);
+
+    // jumps that don't match each other aren't loops
+    assert_eq!(Program::parse("MOO moo MoO moo").analyze().loops(), []);
+}
+
+#[test]
+fn provably_zero_cells() {
+    let analysis = Program::parse("oom moO oom moO OOO mOo mOo OOM").analyze();
+
+    assert!(analysis.is_zero(7, 2));
+    assert!(analysis.is_zero(7, 5));
+    assert!(!analysis.is_zero(7, 0));
+    assert!(!analysis.is_zero(7, 1));
+}
+
+#[test]
+fn unreachable_commands_have_no_point() {
+    let analysis = Program::parse("MOO OOM moo MoO MOO MoO moo OOM").analyze();
+
+    assert!(analysis.is_reachable(0));
+    assert!(!analysis.is_reachable(1));
+    assert!(analysis.is_reachable(3));
+    assert!(analysis.point(7).is_none());
+    assert!(analysis.exit().is_none());
+}
+
+#[test]
+fn dispatch_follows_the_current_value() {
+    let dispatch = |program: &str, idx| {
+        Program::parse(program)
+            .analyze()
+            .point(idx)
+            .unwrap()
+            .dispatch()
+    };
+
+    let known = dispatch("MoO MoO mOO", 2);
+    assert_eq!(known.commands, [Command::moO]);
+    assert_eq!(known.only(), Some(Command::moO));
+
+    let recursive = dispatch("MoO MoO MoO mOO", 3);
+    assert!(recursive.commands.is_empty());
+    assert!(recursive.may_recurse && !recursive.may_be_invalid);
+
+    let nonzero = dispatch("oom MoO mOO", 2);
+    assert_eq!(nonzero.commands.len(), 10);
+    assert!(!nonzero.commands.contains(&Command::moo));
+    assert!(nonzero.may_recurse && nonzero.may_be_invalid);
+    assert_eq!(nonzero.only(), None);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use cowlang::{
+    Program,
+    asm::{self, Error, ErrorKind},
+};
+
+fn output(source: &str, input: &str) -> String {
+    common::output(asm::assemble(source).unwrap(), input)
+}
+
+fn error(source: &str) -> (usize, usize, ErrorKind) {
+    let Error { line, column, kind } = asm::assemble(source).unwrap_err();
+    (line, column, kind)
+}
+
+#[test]
+fn instructions_map_to_commands() {
+    assert_eq!(
+        asm::assemble("add 2 sub right 3 left clear swap print read char eval raw MoO OOM")
+            .unwrap()
+            .to_string(),
+        "MoO MoO MOo moO moO moO mOo OOO MMM OOM oom Moo mOO MoO OOM",
+    );
+}
+
+#[test]
+fn loops_multiply() {
+    let source = "
+        ; 72 = 8 * 9
+        right add 8
+        loop {
+            left add 9
+            right sub
+        }
+        left char ; H
+    ";
+
+    assert_eq!(output(source, ""), "H");
+}
+
+#[test]
+fn nested_and_empty_loops_are_padded() {
+    let program = asm::assemble("loop { loop {} } add print").unwrap();
+    assert_eq!(
+        &*program,
+        &*Program::parse("MOO moO mOo MOO moO mOo moo moO mOo moo MoO OOM")
+    );
+    assert_eq!(output("loop { loop {} } add print", ""), "1");
+}
+
+#[test]
+fn labels_name_cells() {
+    let source = "
+        counter: add 3
+        right 2 total:
+        goto counter
+        loop {
+            goto total add 2
+            goto counter sub
+        }
+        goto total print
+    ";
+
+    assert_eq!(output(source, ""), "6");
+}
+
+#[test]
+fn errors_point_at_the_source() {
+    assert_eq!(
+        error("add\n  moo"),
+        (2, 3, ErrorKind::UnknownInstruction("moo".to_string()))
+    );
+    assert_eq!(
+        error("add 99999999999999999999999"),
+        (
+            1,
+            5,
+            ErrorKind::InvalidCount("99999999999999999999999".to_string())
+        )
+    );
+    assert_eq!(error("loop add"), (1, 1, ErrorKind::ExpectedOpenBrace));
+    assert_eq!(error("loop {\nadd"), (1, 6, ErrorKind::UnclosedLoop));
+    assert_eq!(error("add }"), (1, 5, ErrorKind::UnexpectedCloseBrace));
+    assert_eq!(error("raw add"), (1, 1, ErrorKind::ExpectedCommand));
+    assert_eq!(
+        error("goto nowhere"),
+        (1, 6, ErrorKind::UnknownLabel("nowhere".to_string()))
+    );
+    assert_eq!(
+        error("a: right a:"),
+        (1, 10, ErrorKind::DuplicateLabel("a".to_string()))
+    );
+}
+
+#[test]
+fn programs_are_limited_in_length() {
+    let limit = asm::MAX_LEN.to_string();
+    assert_eq!(
+        asm::assemble(&format!("add {limit}")).unwrap().len(),
+        asm::MAX_LEN
+    );
+    assert_eq!(
+        error(&format!("clear add {limit}")),
+        (1, 11, ErrorKind::TooLong)
+    );
+    assert_eq!(error("add 99999999999"), (1, 5, ErrorKind::TooLong));
+
+    let half = (asm::MAX_LEN / 2).to_string();
+    assert_eq!(
+        error(&format!("a: right {half} b: goto a goto b")),
+        (1, 28, ErrorKind::TooLong)
+    );
+    assert_eq!(
+        asm::assemble("add 99999999999").unwrap_err().to_string(),
+        format!("1:5: program is longer than {limit} commands")
+    );
+}
+
+#[test]
+fn labels_need_a_known_position() {
+    assert_eq!(error("a: eval goto a"), (1, 9, ErrorKind::UnknownPosition));
+    assert_eq!(
+        error("a: loop { right } goto a"),
+        (1, 19, ErrorKind::UnknownPosition)
+    );
+    assert_eq!(
+        error("a: loop { goto a right }"),
+        (1, 4, ErrorKind::UnbalancedLoop)
+    );
+    assert_eq!(
+        asm::assemble("a: eval goto a").unwrap_err().to_string(),
+        "1:9: the cursor position is not known here, so labels cannot be used"
+    );
+}
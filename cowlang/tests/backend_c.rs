@@ -0,0 +1,135 @@
+mod common;
+
+use common::interpret;
+use cowlang::Program;
+use std::{
+    io::Write,
+    path::Path,
+    process::{self, Stdio},
+};
+
+const PROGRAMS: &[(&str, &str, &str)] = &[
+    (
+        "hello_world",
+        include_str!("../../samples/hello-world.txt"),
+        "",
+    ),
+    (
+        "hello_world_loops",
+        include_str!("../../samples/hello-world-loops.txt"),
+        "",
+    ),
+    ("echo", "Moo Moo moO Moo Moo moO oom OOM", "é!\n12\n"),
+    ("saturate", "MOo oom MoO OOM mOo mOo OOM", "4294967295\n"),
+    ("register", "MoO MoO MoO MMM moO MMM OOM MMM", ""),
+    ("skip", "MOO moo MoO moo MoO OOM MoO MOO MOo OOM moo", ""),
+    (
+        "eval",
+        "MoO MOO OOM MOo mOO moo MoO MoO MoO MoO MoO MoO MoO MoO MoO MoO mOO",
+        "",
+    ),
+    ("eval_read", "oom mOO OOM", "11\n7\n"),
+    (
+        "eval_known",
+        "MoO MoO mOO OOM moO MoO MoO MoO MoO MoO MoO MoO mOO OOM",
+        "",
+    ),
+    ("recursive_eval", "MoO MoO MoO mOO", ""),
+    ("invalid_command", "oom mOO", "12\n"),
+    ("beginless", "MoO OOM moo", ""),
+    ("endless", "MOO MoO", ""),
+    ("unwritable", "oom Moo", "55296\n"),
+    ("eof", "Moo", ""),
+    ("bad_int", "oom", "+x\n"),
+    (
+        "long_int",
+        "oom OOM",
+        "                                                                  0000000000000042\n",
+    ),
+    ("nul_int", "oom", "1\x002\n"),
+];
+
+/// Inputs that aren't valid UTF-8.
+const RAW_INPUTS: &[(&str, &str, &[u8])] = &[
+    ("overlong", "Moo", b"\xC0\xAF"),
+    ("surrogate", "Moo", b"\xED\xA0\x80"),
+    ("too_high", "Moo", b"\xF4\x90\x80\x80"),
+    ("truncated", "Moo", b"\xE0A"),
+];
+
+fn compiled(dir: &Path, name: &str, program: &str, input: &[u8]) -> (Vec<u8>, Option<String>) {
+    let source = dir.join(format!("{name}.c"));
+    let binary = dir.join(name);
+
+    std::fs::write(
+        &source,
+        cowlang::backend::c::compile(&Program::parse(program)),
+    )
+    .unwrap();
+
+    let status = process::Command::new("cc")
+        .args(["-std=c11", "-Wall", "-Werror", "-O1", "-o"])
+        .arg(&binary)
+        .arg(&source)
+        .status()
+        .unwrap();
+    assert!(status.success(), "{name}: failed to compile");
+
+    let mut child = process::Command::new(&binary)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    let error = (!output.status.success()).then(|| {
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .trim()
+            .trim_start_matches("error: ")
+            .to_string()
+    });
+
+    (output.stdout, error)
+}
+
+#[test]
+fn compiled_programs_match_the_interpreter() {
+    if process::Command::new("cc")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        eprintln!("skipping: no C compiler found");
+        return;
+    }
+
+    let dir = std::env::temp_dir().join(format!("cowlang-backend-c-{}", process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let programs = PROGRAMS
+        .iter()
+        .map(|&(name, program, input)| (name, program, input.as_bytes()))
+        .chain(RAW_INPUTS.iter().copied());
+
+    for (name, program, input) in programs {
+        assert_eq!(
+            compiled(&dir, name, program, input),
+            interpret(program, input),
+            "{name}"
+        );
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn known_evaluations_skip_the_dispatch() {
+    let compile = |program| cowlang::backend::c::compile(&Program::parse(program));
+
+    assert!(!compile("MoO MoO mOO OOM").contains("switch"));
+    assert!(!compile("MoO MoO MoO mOO OOM").contains("switch"));
+    assert!(compile("oom mOO OOM").contains("switch"));
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::interpret;
+use cowlang::{Program, Reader, Writer};
+use std::io::Cursor;
+
+mod eval {
+    include!("generated/eval.rs");
+}
+
+mod hello_world_loops {
+    include!("generated/hello_world_loops.rs");
+}
+
+const EVAL: &str = "oom mOO OOM MoO MOO OOM MOo mOO moo Moo Moo moO MoO MoO MMM moO MMM OOM";
+const HELLO_WORLD_LOOPS: &str = include_str!("../../samples/hello-world-loops.txt");
+
+type Run = fn(&mut dyn cowlang::Input, &mut dyn cowlang::Output) -> Result<(), cowlang::Error>;
+
+fn compiled(run: Run, input: &str) -> (Vec<u8>, Option<String>) {
+    let mut input = Reader::new(Cursor::new(input.as_bytes()));
+    let mut output = Writer::new(Vec::new());
+
+    let error = run(&mut input, &mut output)
+        .err()
+        .map(|error| error.to_string());
+    (output.into_inner(), error)
+}
+
+#[test]
+fn generated_code_is_up_to_date() {
+    for (program, generated) in [
+        (EVAL, include_str!("generated/eval.rs")),
+        (
+            HELLO_WORLD_LOOPS,
+            include_str!("generated/hello_world_loops.rs"),
+        ),
+    ] {
+        assert_eq!(
+            cowlang::backend::rust::compile(&Program::parse(program)),
+            generated,
+            "regenerate with `cowlang-cli compile --target rust`"
+        );
+    }
+}
+
+#[test]
+fn compiled_programs_match_the_interpreter() {
+    assert_eq!(
+        compiled(hello_world_loops::run, ""),
+        interpret(HELLO_WORLD_LOOPS, "")
+    );
+
+    for input in ["2\nx", "10\n", "0\n", "12\n", "2\n", ""] {
+        assert_eq!(
+            compiled(eval::run, input),
+            interpret(EVAL, input),
+            "{input:?}"
+        );
+    }
+}
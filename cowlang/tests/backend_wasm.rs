@@ -0,0 +1,143 @@
+mod common;
+
+use common::interpret;
+use cowlang::{Input, Output, Program, Reader, Writer};
+use std::io::Cursor;
+use wasmi::{Caller, Engine, Linker, Module, Store};
+
+const PROGRAMS: &[(&str, &str, &str)] = &[
+    (
+        "hello_world",
+        include_str!("../../samples/hello-world.txt"),
+        "",
+    ),
+    (
+        "hello_world_loops",
+        include_str!("../../samples/hello-world-loops.txt"),
+        "",
+    ),
+    ("echo", "Moo Moo moO Moo Moo moO oom OOM", "é!\n12\n"),
+    ("saturate", "MOo oom MoO OOM mOo mOo OOM", "4294967295\n"),
+    ("register", "MoO MoO MoO MMM moO MMM OOM MMM", ""),
+    ("skip", "MOO moo MoO moo MoO OOM MoO MOO MOo OOM moo", ""),
+    (
+        "eval",
+        "MoO MOO OOM MOo mOO moo MoO MoO MoO MoO MoO MoO MoO MoO MoO MoO mOO",
+        "",
+    ),
+    ("eval_read", "oom mOO OOM", "11\n7\n"),
+    (
+        "eval_known",
+        "MoO MoO mOO OOM moO MoO MoO MoO MoO MoO MoO MoO mOO OOM",
+        "",
+    ),
+    ("eval_moo", "oom mOO OOM MoO MOO OOM MOo mOO moo", "2\n"),
+    ("recursive_eval", "MoO MoO MoO mOO", ""),
+    ("invalid_command", "oom mOO", "12\n"),
+    ("beginless", "MoO OOM moo", ""),
+    ("endless", "MOO MoO", ""),
+    ("unwritable", "oom Moo", "55296\n"),
+    ("eof", "Moo", ""),
+    ("bad_int", "oom", "+x\n"),
+    ("empty", "", ""),
+];
+
+type Io = (
+    Reader<Cursor<Vec<u8>>>,
+    Writer<Vec<u8>>,
+    Option<cowlang::Error>,
+);
+
+/// Stashes a failed host call's error in the store and traps.
+fn host<T>(caller: &mut Caller<'_, Io>, result: std::io::Result<T>) -> Result<T, wasmi::Error> {
+    result.map_err(|error| {
+        let message = error.to_string();
+        caller.data_mut().2 = Some(error.into());
+        wasmi::Error::new(message)
+    })
+}
+
+fn compiled(program: &str, input: &str) -> (Vec<u8>, Option<String>) {
+    let wasm = cowlang::backend::wasm::compile(&Program::parse(program));
+
+    let engine = Engine::default();
+    let module = Module::new(&engine, &wasm[..]).unwrap();
+    let mut store = Store::new(
+        &engine,
+        (
+            Reader::new(Cursor::new(input.as_bytes().to_vec())),
+            Writer::new(Vec::new()),
+            None,
+        ),
+    );
+
+    let mut linker = Linker::<Io>::new(&engine);
+    linker
+        .func_wrap("env", "input_char", |mut caller: Caller<'_, Io>| {
+            let result = caller.data_mut().0.input_char();
+            host(&mut caller, result).map(|char| char as i32)
+        })
+        .unwrap()
+        .func_wrap("env", "input_int", |mut caller: Caller<'_, Io>| {
+            let result = caller.data_mut().0.input_int();
+            host(&mut caller, result).map(|int| int as i32)
+        })
+        .unwrap()
+        .func_wrap(
+            "env",
+            "output_char",
+            |mut caller: Caller<'_, Io>, value: i32| {
+                let char = char::from_u32(value as u32).unwrap();
+                let result = caller.data_mut().1.output_char(char);
+                host(&mut caller, result)
+            },
+        )
+        .unwrap()
+        .func_wrap(
+            "env",
+            "output_int",
+            |mut caller: Caller<'_, Io>, value: i32| {
+                let result = caller.data_mut().1.output_int(value as u32);
+                host(&mut caller, result)
+            },
+        )
+        .unwrap();
+
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .unwrap()
+        .start(&mut store)
+        .unwrap();
+    let run = instance.get_typed_func::<(), i32>(&store, "run").unwrap();
+
+    let error = match run.call(&mut store, ()) {
+        Ok(code) => cowlang::backend::wasm::error(code),
+        Err(trap) => Some(
+            store
+                .data_mut()
+                .2
+                .take()
+                .unwrap_or_else(|| panic!("{trap}")),
+        ),
+    };
+
+    let (_, output, _) = store.into_data();
+    (output.into_inner(), error.map(|error| error.to_string()))
+}
+
+#[test]
+fn compiled_programs_match_the_interpreter() {
+    for &(name, program, input) in PROGRAMS {
+        assert_eq!(
+            compiled(program, input),
+            interpret(program, input),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn memory_grows_past_the_first_page() {
+    let program = format!("{} MoO OOM mOo MoO OOM", "moO ".repeat(20_000));
+    assert_eq!(compiled(&program, ""), interpret(&program, ""));
+}
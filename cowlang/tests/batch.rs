@@ -0,0 +1,134 @@
+use cowlang::{
+    Error, Program,
+    batch::{self, Case, Job, Options, Verdict},
+};
+use std::num::NonZeroUsize;
+
+fn job(source: &str, expected: &str) -> Job<'static> {
+    Job {
+        name: source.to_string(),
+        program: Program::parse(source),
+        case: Case {
+            name: "case".to_string(),
+            input: String::new(),
+            expected: expected.to_string(),
+            max_steps: None,
+        },
+    }
+}
+
+fn verdict(job: Job, options: &Options) -> Verdict {
+    let mut outcomes = batch::run(&[job], options);
+    assert_eq!(outcomes.len(), 1);
+    outcomes.remove(0).verdict
+}
+
+#[test]
+fn outcomes_keep_the_order_of_their_jobs() {
+    let jobs = (0..50)
+        .map(|n| {
+            let source = format!("{} OOM", vec!["MoO"; n].join(" "));
+            job(&source, &n.to_string())
+        })
+        .collect::<Vec<_>>();
+    let options = Options {
+        threads: NonZeroUsize::new(4).unwrap(),
+        ..Options::default()
+    };
+
+    let outcomes = batch::run(&jobs, &options);
+
+    assert_eq!(outcomes.len(), jobs.len());
+    for (outcome, job) in outcomes.iter().zip(&jobs) {
+        assert_eq!(outcome.name, job.name);
+        assert!(outcome.passed(), "{}: {:?}", outcome.name, outcome.verdict);
+    }
+}
+
+#[test]
+fn verdicts_describe_how_the_run_ended() {
+    let options = Options::default();
+
+    assert!(matches!(
+        verdict(job("MoO MoO OOM", "2"), &options),
+        Verdict::Pass
+    ));
+    assert!(matches!(
+        verdict(job("MoO OOM", "2"), &options),
+        Verdict::Fail { diff } if diff == "-2\n\\ No newline at end of output\n+1\n\\ No newline at end of output\n"
+    ));
+    assert!(matches!(
+        verdict(job("MOO", ""), &options),
+        Verdict::Error(Error::EndlessJumpForward)
+    ));
+    assert!(matches!(
+        verdict(job("MoO MOO mOo moo", ""), &options),
+        Verdict::StepLimit
+    ));
+    assert!(matches!(
+        verdict(job("MoO MOO moO MoO MoO moo", ""), &options),
+        Verdict::MemoryLimit
+    ));
+}
+
+#[test]
+fn memory_grown_by_the_last_step_is_limited() {
+    let options = Options {
+        max_memory: 3,
+        ..Options::default()
+    };
+    assert!(matches!(
+        verdict(job("moO moO", ""), &options),
+        Verdict::Pass
+    ));
+    assert!(matches!(
+        verdict(job("moO moO moO", ""), &options),
+        Verdict::MemoryLimit
+    ));
+
+    let options = Options {
+        max_steps: 2,
+        max_memory: 2,
+        ..options
+    };
+    assert!(matches!(
+        verdict(job("moO moO moO moO", ""), &options),
+        Verdict::MemoryLimit
+    ));
+}
+
+#[test]
+fn case_step_limits_compare_the_output_so_far() {
+    let mut job = job("MoO MOO OOM moo", "111");
+    job.case.max_steps = Some(7);
+
+    assert!(matches!(verdict(job, &Options::default()), Verdict::Pass));
+}
+
+#[test]
+fn diffs_mark_removed_and_added_lines() {
+    assert_eq!(batch::diff("a\nb\n", "a\nb\n"), " a\n b\n");
+    assert_eq!(
+        batch::diff("a\nb\nc\nd\n", "a\nx\nc\nd\ne\n"),
+        " a\n-b\n+x\n c\n d\n+e\n"
+    );
+    assert_eq!(
+        batch::diff("a\nb", "a\nb\n"),
+        " a\n-b\n\\ No newline at end of output\n+b\n"
+    );
+}
+
+#[test]
+fn large_diffs_fall_back_to_removed_then_added() {
+    let lines = |range: std::ops::Range<usize>| range.map(|n| format!("{n}\n")).collect::<String>();
+    let expected = lines(0..100_000);
+    let actual = format!("start\n{}end\n", lines(0..100_000));
+
+    let diff = batch::diff(&expected, &actual);
+    let removed = diff.lines().filter(|line| line.starts_with('-')).count();
+    let added = diff.lines().filter(|line| line.starts_with('+')).count();
+
+    assert!(diff.starts_with("-0\n-1\n"));
+    assert!(diff.ends_with("+99999\n+end\n"));
+    assert_eq!((removed, added), (100_000, 100_002));
+}
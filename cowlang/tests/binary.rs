@@ -0,0 +1,69 @@
+use cowlang::{
+    Command, Program,
+    binary::{self, Error, MAGIC, VERSION},
+};
+
+fn header(len: u64) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(VERSION);
+    bytes.extend(len.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn commands_are_packed_two_to_a_byte() {
+    let program = Program::parse("moo mOO oom MOO OOM");
+    let mut expected = header(5);
+    expected.extend([0x03, 0xb7, 0xa0]);
+
+    assert_eq!(binary::encode(&program), expected);
+    assert_eq!(&*binary::decode(&expected).unwrap(), &*program);
+}
+
+#[test]
+fn empty_programs_are_just_a_header() {
+    assert_eq!(binary::encode(&[]), header(0));
+    assert!(binary::decode(&header(0)).unwrap().is_empty());
+}
+
+#[test]
+fn bad_data_is_rejected() {
+    let with_body = |len, body: &[u8]| {
+        let mut bytes = header(len);
+        bytes.extend(body);
+        binary::decode(&bytes).unwrap_err()
+    };
+
+    assert!(matches!(
+        binary::decode(b"MoO MoO").unwrap_err(),
+        Error::NotEncoded
+    ));
+    assert!(matches!(
+        binary::decode(&[&MAGIC[..], &[VERSION + 1]].concat()).unwrap_err(),
+        Error::UnsupportedVersion(2)
+    ));
+    assert!(matches!(
+        binary::decode(&header(0)[..8]).unwrap_err(),
+        Error::Truncated
+    ));
+    assert!(matches!(with_body(3, &[0x12]), Error::Truncated));
+    assert!(matches!(with_body(2, &[0x12, 0x34]), Error::TrailingData));
+    assert!(matches!(with_body(1, &[0x15]), Error::TrailingData));
+    assert!(matches!(
+        with_body(2, &[0x1c]),
+        Error::InvalidCommand { idx: 1, value: 12 }
+    ));
+}
+
+#[test]
+fn programs_can_be_saved_and_loaded() {
+    let path = std::env::temp_dir().join(format!("cowlang-binary-{}.cowb", std::process::id()));
+    let program = Program::new(vec![Command::MoO; 1001]);
+
+    binary::save(&program, &path).unwrap();
+    let loaded = binary::load(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(&*loaded.unwrap(), &*program);
+    assert!(matches!(binary::load(&path).unwrap_err(), Error::Io(_)));
+}
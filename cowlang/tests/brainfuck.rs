@@ -0,0 +1,83 @@
+mod common;
+
+use common::output;
+use cowlang::{
+    Builder, Command, Program,
+    brainfuck::{self, Untranslatable},
+};
+
+const HELLO_WORLD: &str = "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.";
+
+#[test]
+fn translated_brainfuck_runs() {
+    assert_eq!(
+        output(brainfuck::to_program(HELLO_WORLD), ""),
+        "Hello World!\n"
+    );
+}
+
+#[test]
+fn adjacent_brackets_are_padded() {
+    let program = brainfuck::to_program("+++[>++[-]<-]>[]++[[-]],.");
+    assert_eq!(output(program, "x"), "x");
+
+    assert_eq!(
+        &*brainfuck::to_program("[][[]]"),
+        Program::parse("MOO moO mOo moo MOO moO mOo MOO moO mOo moo moO mOo moo").as_ref(),
+    );
+}
+
+#[test]
+fn comments_are_ignored() {
+    assert_eq!(
+        &*brainfuck::to_program("add one: + then print it ."),
+        [Command::MoO, Command::Moo]
+    );
+}
+
+#[test]
+fn programs_round_trip() {
+    let program = brainfuck::to_program(HELLO_WORLD);
+    let source = brainfuck::from_program(&program).unwrap();
+    assert_eq!(brainfuck::to_program(&source).as_ref(), program.as_ref());
+
+    assert_eq!(
+        brainfuck::from_program(&Program::parse("OOO Moo OOO")).unwrap(),
+        ",[-]"
+    );
+}
+
+#[test]
+fn untranslatable_commands_are_reported() {
+    let error = brainfuck::from_program(&Program::parse("MoO mOO MMM moO OOM oom")).unwrap_err();
+    assert_eq!(
+        error.commands(),
+        [
+            (1, Command::mOO),
+            (2, Command::MMM),
+            (4, Command::OOM),
+            (5, Command::oom),
+        ]
+    );
+    assert_eq!(
+        error.to_string(),
+        "cannot translate mOO at 1, MMM at 2, OOM at 4, oom at 5"
+    );
+}
+
+#[test]
+fn loops_that_jump_differently_are_reported() {
+    let error: Untranslatable = brainfuck::from_program(&Program::parse("MOO moo")).unwrap_err();
+    assert_eq!(error.commands(), [(1, Command::moo)]);
+
+    let error =
+        brainfuck::from_program(&Program::parse("MOO MoO MOO MoO moo moo MOO")).unwrap_err();
+    assert_eq!(error.commands(), [(5, Command::moo), (6, Command::MOO)]);
+
+    let padded = {
+        let mut builder = Builder::new();
+        builder.extend(Program::parse("MOO MoO MOO MoO moo moo").iter().copied());
+        builder.build()
+    };
+    assert_eq!(brainfuck::from_program(&padded).unwrap(), "[+[+]><]");
+}
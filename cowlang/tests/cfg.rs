@@ -0,0 +1,156 @@
+use cowlang::{
+    Command, Program,
+    cfg::{self, Block, Edge, EdgeKind, Node},
+};
+
+fn edges(program: &str) -> Vec<(usize, Node, EdgeKind)> {
+    cfg::build(&Program::parse(program))
+        .edges
+        .into_iter()
+        .map(|Edge { from, to, kind }| (from, to, kind))
+        .collect()
+}
+
+#[test]
+fn loops_split_blocks() {
+    let graph = cfg::build(&Program::parse("MoO MOO MOo moo OOM"));
+
+    assert_eq!(
+        graph.blocks,
+        [
+            Block {
+                start: 0,
+                commands: vec![Command::MoO],
+            },
+            Block {
+                start: 1,
+                commands: vec![Command::MOO],
+            },
+            Block {
+                start: 2,
+                commands: vec![Command::MOo, Command::moo],
+            },
+            Block {
+                start: 4,
+                commands: vec![Command::OOM],
+            },
+        ]
+    );
+    assert_eq!(
+        edges("MoO MOO MOo moo OOM"),
+        [
+            (0, Node::Block(1), EdgeKind::Next),
+            (1, Node::Block(2), EdgeKind::Next),
+            (1, Node::Block(3), EdgeKind::Forward),
+            (2, Node::Block(1), EdgeKind::Backward),
+            (3, Node::Exit, EdgeKind::Next),
+        ]
+    );
+}
+
+#[test]
+fn jumps_follow_the_interpreter() {
+    // a `moo` directly after a `MOO` closes both loops, and skips over that
+    // `MOO` when jumping back
+    assert_eq!(
+        edges("MOO MOO moo OOM"),
+        [
+            (0, Node::Block(1), EdgeKind::Next),
+            (0, Node::Block(3), EdgeKind::Forward),
+            (1, Node::Block(2), EdgeKind::Next),
+            (2, Node::Block(0), EdgeKind::Backward),
+            (3, Node::Exit, EdgeKind::Next),
+        ]
+    );
+    // unmatched jumps have no edge
+    assert_eq!(edges("OOM moo"), []);
+    assert_eq!(
+        edges("MOO OOM"),
+        [
+            (0, Node::Block(1), EdgeKind::Next),
+            (1, Node::Exit, EdgeKind::Next)
+        ]
+    );
+}
+
+#[test]
+fn evaluation_may_jump() {
+    assert_eq!(
+        edges("MOO OOM mOO moo OOM"),
+        [
+            (0, Node::Block(1), EdgeKind::Next),
+            (0, Node::Block(3), EdgeKind::Forward),
+            (1, Node::Block(2), EdgeKind::Next),
+            (1, Node::Block(0), EdgeKind::Eval),
+            (2, Node::Block(0), EdgeKind::Backward),
+            (3, Node::Exit, EdgeKind::Next),
+        ]
+    );
+    assert_eq!(
+        edges("mOO MoO moo OOM"),
+        [
+            (0, Node::Block(1), EdgeKind::Next),
+            (2, Node::Exit, EdgeKind::Next),
+        ]
+    );
+}
+
+#[test]
+fn evaluation_never_jumps_forward() {
+    // the `mOO` could only get past the `moo` by evaluating `MOO`, which sees
+    // the value 7
+    let from_eval = edges("MOO OOM mOO MoO moo OOM")
+        .into_iter()
+        .filter(|&(from, ..)| from == 1)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        from_eval,
+        [
+            (1, Node::Block(2), EdgeKind::Next),
+            (1, Node::Block(0), EdgeKind::Eval),
+        ]
+    );
+}
+
+#[test]
+fn dot_output() {
+    let dot = cfg::build(&Program::parse("MoO MOO MOo moo OOM")).to_dot();
+
+    assert_eq!(
+        dot,
+        "digraph cow {
+    node [shape=box, fontname=monospace];
+    exit [shape=doublecircle, label=\"exit\"];
+    b0 [label=\"0: MoO\\l\"];
+    b1 [label=\"1: MOO\\l\"];
+    b2 [label=\"2: MOo moo\\l\"];
+    b3 [label=\"4: OOM\\l\"];
+    b0 -> b1;
+    b1 -> b2;
+    b1 -> b3 [label=\"0\", style=dashed];
+    b2 -> b1 [label=\"moo\", color=blue];
+    b3 -> exit;
+}
+"
+    );
+}
+
+#[test]
+fn json_output() {
+    let graph = cfg::build(&Program::parse(include_str!("../../samples/fibonacci.txt")));
+    let json = serde_json::from_str::<serde_json::Value>(&graph.to_json()).unwrap();
+
+    let blocks = json["blocks"].as_array().unwrap();
+    assert_eq!(blocks.len(), graph.blocks.len());
+    assert_eq!(blocks[0]["start"], 0);
+    assert_eq!(blocks[0]["commands"][0], "MoO");
+
+    let edges = json["edges"].as_array().unwrap();
+    assert_eq!(edges.len(), graph.edges.len());
+    assert!(edges.iter().any(|edge| edge["kind"] == "backward"));
+
+    let empty = cfg::build(&[]).to_json();
+    assert_eq!(empty, "{\n  \"blocks\": [],\n  \"edges\": []\n}\n");
+    serde_json::from_str::<serde_json::Value>(&empty).unwrap();
+}
@@ -0,0 +1,31 @@
+//! Helpers shared by the integration tests. Each test only uses some of them.
+#![allow(dead_code)]
+
+use cowlang::{Cowlang, Dialect, Options, Program, Reader, Writer};
+use std::io::Cursor;
+
+/// Runs `program` on `input`, returning what it printed and the error it
+/// stopped with, if any.
+pub fn run(program: Program, input: impl AsRef<[u8]>) -> (Vec<u8>, Option<String>) {
+    let mut interp = Cowlang::new(Options {
+        program,
+        input: Reader::new(Cursor::new(input)),
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::REFERENCE,
+    });
+
+    let error = interp.run().err().map(|error| error.to_string());
+    (interp.into_io().1.into_inner(), error)
+}
+
+/// Parses and runs `program`, like [`run`].
+pub fn interpret(program: &str, input: impl AsRef<[u8]>) -> (Vec<u8>, Option<String>) {
+    run(Program::parse(program), input)
+}
+
+/// Runs `program` on `input`, which must succeed, returning what it printed.
+pub fn output(program: Program, input: &str) -> String {
+    let (output, error) = run(program, input);
+    assert_eq!(error, None);
+    String::from_utf8(output).unwrap()
+}
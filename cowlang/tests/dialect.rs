@@ -0,0 +1,91 @@
+#![allow(non_snake_case)]
+
+use cowlang::{
+    Cowlang, Dialect, Error, Jumps, LoopEnd, OnRecursiveEval, Options, Program, Reader, Status,
+    Writer,
+};
+use std::io::Cursor;
+
+type Interp = Cowlang<'static, Reader<Cursor<String>>, Writer<Vec<u8>>>;
+
+fn interp(program: &str, dialect: Dialect) -> Interp {
+    Cowlang::new(Options {
+        program: Program::parse(program),
+        input: Reader::new(Cursor::new(String::new())),
+        output: Writer::new(Vec::new()),
+        dialect,
+    })
+}
+
+fn run(program: &str, dialect: Dialect) -> Result<String, Error> {
+    let mut interp = interp(program, dialect);
+    interp.run()?;
+    Ok(String::from_utf8(interp.into_io().1.into_inner()).unwrap())
+}
+
+#[test]
+fn reference_jumps_skip_the_command_after_MOO() {
+    let matching = Dialect {
+        jumps: Jumps::Matching,
+        ..Dialect::REFERENCE
+    };
+
+    assert!(matches!(
+        run("MOO moo OOM", Dialect::REFERENCE),
+        Err(Error::EndlessJumpForward)
+    ));
+    assert_eq!(run("MOO moo OOM", matching).unwrap(), "0");
+}
+
+#[test]
+fn reference_moo_evaluates_MOO_again() {
+    let test = Dialect {
+        loop_end: LoopEnd::Test,
+        ..Dialect::REFERENCE
+    };
+
+    // the evaluated `MOO` jumps forward to the first `moo`, which jumps back
+    let mut reference = interp("MOO MoO moo moo", Dialect::REFERENCE);
+    for _ in 0..100 {
+        assert_eq!(reference.advance().unwrap(), Status::Running);
+    }
+
+    assert_eq!(run("MOO MoO moo moo", test).unwrap(), "");
+}
+
+#[test]
+fn recursive_eval_can_end_or_be_ignored() {
+    let with = |recursive_eval| Dialect {
+        recursive_eval,
+        ..Dialect::REFERENCE
+    };
+    let program = "MoO MoO MoO mOO OOM";
+
+    assert!(matches!(
+        run(program, with(OnRecursiveEval::Error)),
+        Err(Error::RecursiveEval)
+    ));
+    assert_eq!(run(program, with(OnRecursiveEval::Exit)).unwrap(), "");
+    assert_eq!(run(program, Dialect::REFERENCE).unwrap(), "");
+    assert_eq!(run(program, with(OnRecursiveEval::Ignore)).unwrap(), "3");
+
+    // values above 11 are still invalid
+    assert!(matches!(
+        run(
+            "MoO MoO MoO MoO MoO MoO MoO MoO MoO MoO MoO MoO mOO",
+            with(OnRecursiveEval::Ignore)
+        ),
+        Err(Error::InvalidCommand)
+    ));
+}
+
+#[test]
+fn dialects_agree_on_plain_loops() {
+    let program = "MoO MoO MoO MOO OOM moO MoO MoO MOO MOo moo mOo MOo moo";
+
+    assert_eq!(
+        run(program, Dialect::REFERENCE).unwrap(),
+        run(program, Dialect::STRUCTURED).unwrap()
+    );
+    assert_eq!(run(program, Dialect::STRUCTURED).unwrap(), "321");
+}
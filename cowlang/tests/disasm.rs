@@ -0,0 +1,73 @@
+use cowlang::{
+    Command, Program,
+    disasm::{self, Entry, Jump},
+};
+
+#[test]
+fn entries_show_nesting_and_jumps() {
+    let program = Program::parse("MOO MoO moo MoO moo");
+
+    assert_eq!(
+        disasm::entries(&program),
+        [
+            Entry {
+                idx: 0,
+                command: Command::MOO,
+                depth: 0,
+                jump: Some(Jump::To(2)),
+            },
+            Entry {
+                idx: 1,
+                command: Command::MoO,
+                depth: 1,
+                jump: None,
+            },
+            Entry {
+                idx: 2,
+                command: Command::moo,
+                depth: 0,
+                jump: Some(Jump::To(0)),
+            },
+            Entry {
+                idx: 3,
+                command: Command::MoO,
+                depth: 0,
+                jump: None,
+            },
+            Entry {
+                idx: 4,
+                command: Command::moo,
+                depth: 0,
+                jump: Some(Jump::Unmatched),
+            },
+        ]
+    );
+}
+
+#[test]
+fn jumps_follow_the_interpreter() {
+    // the command next to a jump is skipped when looking for its match
+    let entries = disasm::entries(&Program::parse("MOO moo MoO moo"));
+    assert_eq!(entries[0].jump, Some(Jump::To(3)));
+    assert_eq!(entries[3].jump, Some(Jump::Unmatched));
+
+    let entries = disasm::entries(&Program::parse("MOO MoO moo moo"));
+    assert_eq!(entries[3].jump, Some(Jump::To(0)));
+}
+
+#[test]
+fn listing_is_aligned() {
+    let program = Program::parse("MoO MOO MOo moo OOM");
+
+    assert_eq!(
+        disasm::listing(&program),
+        "\
+idx  op  command  jump    description
+  0   6  MoO              increment the current value
+  1   7  MOO      -> 3    if the current value is 0, skip next command and jump to the matching moo
+  2   5    MOo            decrement the current value
+  3   0  moo      -> 1    jump back to the matching MOO
+  4  10  OOM              write the current value to stdout
+"
+    );
+}
@@ -0,0 +1,178 @@
+#![allow(non_snake_case)]
+
+mod common;
+
+use cowlang::{
+    Command, Cowlang, Dialect, Error, InputKind, Options, Program, Reader, Status, Suspend, Value,
+    Writer,
+};
+use std::io::Cursor;
+
+type Interp = Cowlang<'static, Reader<Cursor<String>>, Writer<Vec<u8>>>;
+
+fn run(program: &str, input: &str) -> (Interp, Result<Status, Error>) {
+    let mut interp = Cowlang::new(Options {
+        program: Program::parse(program),
+        input: Reader::new(Cursor::new(input.to_string())),
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::REFERENCE,
+    });
+    let result = interp.run();
+    (interp, result)
+}
+
+fn output(program: &str, input: &str) -> String {
+    common::output(Program::parse(program), input)
+}
+
+#[test]
+fn mOO_evaluating_moo_jumps_back_from_its_own_position() {
+    assert_eq!(output("MoO MOO OOM MOo mOO moo", ""), "1");
+}
+
+#[test]
+fn mOO_evaluates_the_command_numbered_by_the_current_value() {
+    let increments = "MoO ".repeat(Command::OOM as usize);
+    assert_eq!(output(&format!("{increments} mOO"), ""), "10");
+}
+
+#[test]
+fn mOO_ends_on_itself_and_rejects_unknown_commands() {
+    let (interp, result) = run("MoO MoO MoO mOO OOM", "");
+    assert!(matches!(result, Ok(Status::Completed)));
+    assert_eq!(interp.output().get_ref(), b"");
+
+    let (_, result) = run(&format!("{} mOO", "MoO ".repeat(12)), "");
+    assert!(matches!(result, Err(Error::InvalidCommand)));
+}
+
+#[test]
+fn mOO_counts_the_commands_it_runs() {
+    let (interp, result) = run("MoO MOO OOM MOo mOO moo MoO MoO mOO", "");
+    result.unwrap();
+
+    let dispatches = interp.dispatches();
+    assert_eq!(dispatches.count(4, Command::moo), 1);
+    assert_eq!(dispatches.count(8, Command::moO), 1);
+    assert_eq!(dispatches.count(8, Command::moo), 0);
+    assert_eq!(dispatches.total(), 2);
+    assert_eq!(
+        dispatches.iter().collect::<Vec<_>>(),
+        [(4, Command::moo, 1), (8, Command::moO, 1)]
+    );
+}
+
+#[test]
+fn mOO_counts_a_suspended_command_once() {
+    let program = format!("{} mOO", "MoO ".repeat(Command::oom as usize));
+    let mut interp = Cowlang::new(Options {
+        program: Program::parse(&program),
+        input: Suspend,
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::REFERENCE,
+    });
+
+    assert_eq!(interp.run().unwrap(), Status::NeedsInput(InputKind::Int));
+    assert_eq!(interp.dispatches().total(), 0);
+
+    interp.provide_input(Value::Int(5)).unwrap();
+    assert_eq!(interp.run().unwrap(), Status::Completed);
+    assert_eq!(interp.dispatches().count(11, Command::oom), 1);
+    assert_eq!(interp.dispatches().total(), 1);
+}
+
+#[test]
+fn MMM_with_empty_register_copies_the_current_value() {
+    let (interp, result) = run("MoO MoO MMM", "");
+    result.unwrap();
+    assert_eq!(interp.register(), Some(2));
+    assert_eq!(interp.memory(), [2]);
+
+    let (interp, result) = run("MoO MoO MMM moO MMM", "");
+    result.unwrap();
+    assert_eq!(interp.register(), None);
+    assert_eq!(interp.memory(), [2, 2]);
+}
+
+#[test]
+fn MOO_skips_the_next_command_when_jumping_forward() {
+    assert_eq!(output("MOO MoO moo MoO OOM", ""), "1");
+    assert_eq!(output("MOO moo MoO moo MoO OOM", ""), "1");
+}
+
+#[test]
+fn nested_MOO_is_skipped_over() {
+    assert_eq!(output("MOO MoO MOO MoO moo moo MoO OOM", ""), "1");
+    assert_eq!(
+        output(
+            "MoO MoO MOO moO MoO MoO MOO MOo moo mOo MOo moo moO OOM",
+            ""
+        ),
+        "0"
+    );
+}
+
+#[test]
+fn jumps_without_a_match_fail() {
+    assert!(matches!(
+        run("moo", "").1,
+        Err(Error::BeginlessJumpBackward)
+    ));
+    assert!(matches!(
+        run("MoO moo", "").1,
+        Err(Error::BeginlessJumpBackward)
+    ));
+    assert!(matches!(
+        run("MOO MoO", "").1,
+        Err(Error::EndlessJumpForward)
+    ));
+}
+
+#[test]
+fn values_outside_unicode_are_unwritable() {
+    let (_, result) = run("oom Moo", "55296\n");
+    assert!(matches!(result, Err(Error::UnwritableChar)));
+}
+
+#[test]
+fn cells_saturate() {
+    let (interp, result) = run("MOo mOo oom MoO", "4294967295\n");
+    result.unwrap();
+    assert_eq!(interp.memory(), [u32::MAX]);
+}
+
+#[test]
+fn Moo_reads_into_empty_cells_and_writes_others() {
+    assert_eq!(output("Moo Moo", "é"), "é");
+}
+
+#[test]
+fn suspends_until_input_is_provided() {
+    let mut input = Suspend;
+    let mut output = Writer::new(Vec::new());
+    let mut interp: Cowlang = Cowlang::new(Options {
+        program: Program::parse("oom OOM moO Moo Moo"),
+        input: &mut input,
+        output: &mut output,
+        dialect: Dialect::REFERENCE,
+    });
+
+    assert_eq!(interp.run().unwrap(), Status::NeedsInput(InputKind::Int));
+    assert_eq!(
+        interp.advance().unwrap(),
+        Status::NeedsInput(InputKind::Int)
+    );
+    assert!(matches!(
+        interp.provide_input(Value::Char('x')),
+        Err(Error::UnexpectedInput)
+    ));
+
+    interp.provide_input(Value::Int(7)).unwrap();
+    assert_eq!(interp.run().unwrap(), Status::NeedsInput(InputKind::Char));
+
+    interp.provide_input(Value::Char('!')).unwrap();
+    assert_eq!(interp.run().unwrap(), Status::Completed);
+
+    drop(interp);
+    assert_eq!(output.into_inner(), b"7!");
+}
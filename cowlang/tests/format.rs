@@ -0,0 +1,70 @@
+use cowlang::{
+    Program,
+    format::{self, Options},
+};
+
+const SAMPLES: &[&str] = &[
+    include_str!("../../samples/fibonacci.txt"),
+    include_str!("../../samples/hello-world.txt"),
+    include_str!("../../samples/hello-world-loops.txt"),
+];
+
+#[test]
+fn formatting_samples_keeps_their_programs() {
+    for sample in SAMPLES {
+        let formatted = format::source(sample, &Options::default());
+
+        assert_eq!(&*Program::parse(&formatted), &*Program::parse(sample));
+        assert_eq!(format::source(&formatted, &Options::default()), formatted);
+        assert_eq!(
+            format::read(sample.as_bytes(), &Options::default()).unwrap(),
+            formatted
+        );
+    }
+}
+
+#[test]
+fn read_source_matches_borrowed_source() {
+    let source = "moo MOO\r\n\r\n  a comment MoO  \nOOM";
+    assert_eq!(
+        format::read(source.as_bytes(), &Options::default()).unwrap(),
+        format::source(source, &Options::default())
+    );
+    assert!(format::read(&b"MoO \xff"[..], &Options::default()).is_err());
+}
+
+#[test]
+fn loops_are_indented() {
+    let options = Options {
+        words_per_line: 3,
+        indent: 2,
+    };
+    let program = Program::parse("MoO MOO moO MOO MOo moo MoO MoO MoO MoO mOo moo OOM");
+
+    assert_eq!(
+        format::program(&program, &options),
+        "MoO MOO\n  moO MOO\n    MOo\n  moo MoO MoO\n  MoO MoO mOo\nmoo OOM\n",
+    );
+}
+
+#[test]
+fn unmatched_moo_stays_at_the_margin() {
+    let program = Program::parse("moo MoO moo");
+    assert_eq!(
+        format::program(&program, &Options::default()),
+        "moo MoO\nmoo\n"
+    );
+}
+
+#[test]
+fn comments_and_blank_lines_are_kept() {
+    let source = "  set up:  MoO MoO\n\n\n MOO MOo  moo  loop  done\nOOM print it\n\n";
+    let formatted = format::source(source, &Options::default());
+
+    assert_eq!(
+        formatted,
+        "set up:\nMoO MoO\n\nMOO\n    MOo\nmoo\nloop  done\nOOM\nprint it\n",
+    );
+    assert_eq!(format::source(&formatted, &Options::default()), formatted);
+    assert_eq!(&*Program::parse(&formatted), &*Program::parse(source));
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::output;
+use cowlang::{
+    Command, Program,
+    generate::{self, ContainsNul, Style},
+};
+
+const TEXTS: &[&str] = &[
+    "",
+    "a",
+    "Hello, World!",
+    "Hello,\u{a0}world!\n",
+    "moo moo MOO\tmoo\n",
+    "¡Olé! 牛",
+];
+
+#[test]
+fn both_styles_print_the_text() {
+    for text in TEXTS {
+        for style in [Style::Compact, Style::Readable] {
+            let program = generate::print(text, style).unwrap();
+            assert_eq!(output(program, ""), *text, "{style:?}");
+        }
+    }
+}
+
+#[test]
+fn compact_programs_are_shorter() {
+    let sample = Program::parse(include_str!("../../samples/hello-world.txt"));
+    let compact = generate::print("Hello,\u{a0}world!", Style::Compact).unwrap();
+    let readable = generate::print("Hello,\u{a0}world!", Style::Readable).unwrap();
+
+    assert!(compact.len() < readable.len());
+    assert!(compact.len() < sample.len() / 2);
+}
+
+#[test]
+fn readable_programs_walk_one_cell() {
+    let program = generate::print("AC", Style::Readable).unwrap();
+
+    let increments = program.iter().filter(|&&command| command == Command::MoO);
+    assert_eq!(increments.count(), 67);
+    assert!(program.ends_with(&Program::parse("MoO MoO Moo")));
+}
+
+#[test]
+fn nul_cannot_be_printed() {
+    assert_eq!(
+        generate::print("ab\0c", Style::Compact).unwrap_err(),
+        ContainsNul { idx: 2 }
+    );
+}
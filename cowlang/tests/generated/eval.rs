@@ -0,0 +1,191 @@
+// Generated by cowlang. Do not edit.
+
+#[allow(unused, clippy::all)]
+pub fn run(
+    input: &mut dyn ::cowlang::Input,
+    output: &mut dyn ::cowlang::Output,
+) -> ::core::result::Result<(), ::cowlang::Error> {
+    let mut memory: ::std::vec::Vec<u32> = ::std::vec![0];
+    let mut memory_idx: usize = 0;
+    let mut register: ::core::option::Option<u32> = None;
+    let mut block: usize = 0;
+
+    loop {
+        match block {
+            0 => {
+                memory[memory_idx] = input.input_int()?;
+                match memory[memory_idx] {
+                    0 => {
+                        return Err(::cowlang::Error::BeginlessJumpBackward);
+                    }
+                    1 => {
+                        memory_idx = memory_idx.saturating_sub(1);
+                        block = 1;
+                    }
+                    2 => {
+                        memory_idx += 1;
+                        if memory_idx >= memory.len() {
+                            memory.resize(memory_idx + 1, 0);
+                        }
+                        block = 1;
+                    }
+                    3 => {
+                        return Ok(());
+                    }
+                    4 => {
+                        if memory[memory_idx] == 0 {
+                            memory[memory_idx] = input.input_char()? as u32;
+                        } else {
+                            output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                        }
+                        block = 1;
+                    }
+                    5 => {
+                        memory[memory_idx] = memory[memory_idx].saturating_sub(1);
+                        block = 1;
+                    }
+                    6 => {
+                        memory[memory_idx] = memory[memory_idx].saturating_add(1);
+                        block = 1;
+                    }
+                    7 => {
+                        block = 1;
+                    }
+                    8 => {
+                        memory[memory_idx] = 0;
+                        block = 1;
+                    }
+                    9 => {
+                        match register.take() {
+                            Some(register_value) => memory[memory_idx] = register_value,
+                            None => register = Some(memory[memory_idx]),
+                        }
+                        block = 1;
+                    }
+                    10 => {
+                        output.output_int(memory[memory_idx])?;
+                        block = 1;
+                    }
+                    11 => {
+                        memory[memory_idx] = input.input_int()?;
+                        block = 1;
+                    }
+                    _ => {
+                        return Err(::cowlang::Error::InvalidCommand);
+                    }
+                }
+            }
+            1 => {
+                output.output_int(memory[memory_idx])?;
+                memory[memory_idx] = memory[memory_idx].saturating_add(1);
+                block = 2;
+            }
+            2 => {
+                if memory[memory_idx] == 0 {
+                    block = 5;
+                } else {
+                    block = 3;
+                }
+            }
+            3 => {
+                output.output_int(memory[memory_idx])?;
+                memory[memory_idx] = memory[memory_idx].saturating_sub(1);
+                match memory[memory_idx] {
+                    0 => {
+                        block = 2;
+                    }
+                    1 => {
+                        memory_idx = memory_idx.saturating_sub(1);
+                        block = 4;
+                    }
+                    2 => {
+                        memory_idx += 1;
+                        if memory_idx >= memory.len() {
+                            memory.resize(memory_idx + 1, 0);
+                        }
+                        block = 4;
+                    }
+                    3 => {
+                        return Ok(());
+                    }
+                    4 => {
+                        if memory[memory_idx] == 0 {
+                            memory[memory_idx] = input.input_char()? as u32;
+                        } else {
+                            output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                        }
+                        block = 4;
+                    }
+                    5 => {
+                        memory[memory_idx] = memory[memory_idx].saturating_sub(1);
+                        block = 4;
+                    }
+                    6 => {
+                        memory[memory_idx] = memory[memory_idx].saturating_add(1);
+                        block = 4;
+                    }
+                    7 => {
+                        block = 4;
+                    }
+                    8 => {
+                        memory[memory_idx] = 0;
+                        block = 4;
+                    }
+                    9 => {
+                        match register.take() {
+                            Some(register_value) => memory[memory_idx] = register_value,
+                            None => register = Some(memory[memory_idx]),
+                        }
+                        block = 4;
+                    }
+                    10 => {
+                        output.output_int(memory[memory_idx])?;
+                        block = 4;
+                    }
+                    11 => {
+                        memory[memory_idx] = input.input_int()?;
+                        block = 4;
+                    }
+                    _ => {
+                        return Err(::cowlang::Error::InvalidCommand);
+                    }
+                }
+            }
+            4 => {
+                block = 2;
+            }
+            5 => {
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                memory_idx += 1;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_add(2);
+                match register.take() {
+                    Some(register_value) => memory[memory_idx] = register_value,
+                    None => register = Some(memory[memory_idx]),
+                }
+                memory_idx += 1;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                match register.take() {
+                    Some(register_value) => memory[memory_idx] = register_value,
+                    None => register = Some(memory[memory_idx]),
+                }
+                output.output_int(memory[memory_idx])?;
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+// Generated by cowlang. Do not edit.
+
+#[allow(unused, clippy::all)]
+pub fn run(
+    input: &mut dyn ::cowlang::Input,
+    output: &mut dyn ::cowlang::Output,
+) -> ::core::result::Result<(), ::cowlang::Error> {
+    let mut memory: ::std::vec::Vec<u32> = ::std::vec![0];
+    let mut memory_idx: usize = 0;
+    let mut register: ::core::option::Option<u32> = None;
+    let mut block: usize = 0;
+
+    loop {
+        match block {
+            0 => {
+                memory[memory_idx] = memory[memory_idx].saturating_add(8);
+                block = 1;
+            }
+            1 => {
+                if memory[memory_idx] == 0 {
+                    block = 3;
+                } else {
+                    block = 2;
+                }
+            }
+            2 => {
+                memory_idx += 1;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_add(5);
+                memory_idx += 1;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_add(4);
+                memory_idx += 1;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_add(4);
+                memory_idx += 1;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_add(9);
+                memory_idx += 1;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_add(4);
+                memory_idx = memory_idx.saturating_sub(5);
+                memory[memory_idx] = memory[memory_idx].saturating_sub(1);
+                block = 1;
+            }
+            3 => {
+                memory_idx += 4;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                memory_idx += 1;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                block = 4;
+            }
+            4 => {
+                if memory[memory_idx] == 0 {
+                    block = 6;
+                } else {
+                    block = 5;
+                }
+            }
+            5 => {
+                memory_idx = memory_idx.saturating_sub(1);
+                memory[memory_idx] = memory[memory_idx].saturating_add(1);
+                memory_idx += 1;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_sub(1);
+                block = 4;
+            }
+            6 => {
+                memory_idx = memory_idx.saturating_sub(1);
+                memory[memory_idx] = memory[memory_idx].saturating_sub(3);
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_add(7);
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_add(3);
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                match register.take() {
+                    Some(register_value) => memory[memory_idx] = register_value,
+                    None => register = Some(memory[memory_idx]),
+                }
+                memory_idx = memory_idx.saturating_sub(3);
+                memory[memory_idx] = memory[memory_idx].saturating_add(4);
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                memory_idx += 1;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                block = 7;
+            }
+            7 => {
+                if memory[memory_idx] == 0 {
+                    block = 9;
+                } else {
+                    block = 8;
+                }
+            }
+            8 => {
+                memory_idx += 2;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_sub(1);
+                memory_idx = memory_idx.saturating_sub(2);
+                memory[memory_idx] = memory[memory_idx].saturating_sub(1);
+                block = 7;
+            }
+            9 => {
+                memory_idx += 2;
+                if memory_idx >= memory.len() {
+                    memory.resize(memory_idx + 1, 0);
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_add(8);
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                match register.take() {
+                    Some(register_value) => memory[memory_idx] = register_value,
+                    None => register = Some(memory[memory_idx]),
+                }
+                match register.take() {
+                    Some(register_value) => memory[memory_idx] = register_value,
+                    None => register = Some(memory[memory_idx]),
+                }
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_add(3);
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                match register.take() {
+                    Some(register_value) => memory[memory_idx] = register_value,
+                    None => register = Some(memory[memory_idx]),
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_sub(3);
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                memory[memory_idx] = memory[memory_idx].saturating_sub(8);
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                memory_idx = memory_idx.saturating_sub(1);
+                memory[memory_idx] = memory[memory_idx].saturating_add(1);
+                if memory[memory_idx] == 0 {
+                    memory[memory_idx] = input.input_char()? as u32;
+                } else {
+                    output.output_char(::core::char::from_u32(memory[memory_idx]).ok_or(::cowlang::Error::UnwritableChar)?)?;
+                }
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+    }
+}
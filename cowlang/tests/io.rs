@@ -0,0 +1,154 @@
+use cowlang::{
+    Cowlang, Dialect, Error, Input, InputKind, Options, Output, Program, Reader, Status, Suspend,
+    Value, Writer,
+};
+use std::io::{self, Cursor};
+
+/// Hands out the ints it holds, then has nothing ready.
+struct Queue(Vec<u32>);
+
+impl Input for Queue {
+    fn input_int(&mut self) -> io::Result<u32> {
+        self.0.pop().ok_or_else(|| io::ErrorKind::WouldBlock.into())
+    }
+
+    fn input_char(&mut self) -> io::Result<char> {
+        Err(io::ErrorKind::WouldBlock.into())
+    }
+}
+
+#[test]
+fn values_know_their_kind() {
+    assert_eq!(Value::Int(0).kind(), InputKind::Int);
+    assert_eq!(Value::Char('a').kind(), InputKind::Char);
+}
+
+#[test]
+fn suspended_programs_stay_put() {
+    let mut interp = Cowlang::new(Options {
+        program: Program::parse("moO Moo"),
+        input: Suspend,
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::REFERENCE,
+    });
+
+    assert_eq!(interp.advance().unwrap(), Status::Running);
+    assert_eq!(interp.run().unwrap(), Status::NeedsInput(InputKind::Char));
+
+    for _ in 0..3 {
+        assert_eq!(
+            interp.advance().unwrap(),
+            Status::NeedsInput(InputKind::Char)
+        );
+    }
+    assert_eq!(interp.memory(), [0, 0]);
+}
+
+#[test]
+fn input_is_only_accepted_when_asked_for() {
+    let mut interp = Cowlang::new(Options {
+        program: Program::parse("oom OOM"),
+        input: Suspend,
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::REFERENCE,
+    });
+
+    assert!(matches!(
+        interp.provide_input(Value::Int(1)),
+        Err(Error::UnexpectedInput)
+    ));
+    assert_eq!(interp.run().unwrap(), Status::NeedsInput(InputKind::Int));
+    assert!(matches!(
+        interp.provide_input(Value::Char('1')),
+        Err(Error::UnexpectedInput)
+    ));
+
+    interp.provide_input(Value::Int(1)).unwrap();
+    assert!(matches!(
+        interp.provide_input(Value::Int(2)),
+        Err(Error::UnexpectedInput)
+    ));
+    assert_eq!(interp.run().unwrap(), Status::Completed);
+    assert_eq!(interp.output().get_ref(), b"1");
+}
+
+#[test]
+fn any_input_can_suspend() {
+    let mut interp = Cowlang::new(Options {
+        program: Program::parse("oom OOM oom OOM"),
+        input: Queue(vec![3]),
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::REFERENCE,
+    });
+
+    assert_eq!(interp.run().unwrap(), Status::NeedsInput(InputKind::Int));
+    interp.provide_input(Value::Int(4)).unwrap();
+    assert_eq!(interp.run().unwrap(), Status::Completed);
+    assert_eq!(interp.output().get_ref(), b"34");
+}
+
+#[test]
+fn readers_decode_lines_and_utf8() {
+    let mut reader = Reader::new(Cursor::new("42\n é\n".as_bytes()));
+    assert_eq!(reader.input_int().unwrap(), 42);
+    assert_eq!(reader.input_char().unwrap(), ' ');
+    assert_eq!(reader.input_char().unwrap(), 'é');
+    assert_eq!(reader.input_char().unwrap(), '\n');
+    assert_eq!(
+        reader.input_int().unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+
+    let mut reader = Reader::new(Cursor::new(&b"cow\n\xff"[..]));
+    assert_eq!(
+        reader.input_int().unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+    assert_eq!(
+        reader.input_char().unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn writers_write_ints_without_separators() {
+    let mut writer = Writer::new(Vec::new());
+    writer.output_int(4).unwrap();
+    writer.output_int(2).unwrap();
+    writer.output_char('!').unwrap();
+    assert_eq!(writer.into_inner(), b"42!");
+}
+
+#[test]
+fn borrowed_and_boxed_io_can_be_mixed() {
+    let mut output = Writer::new(Vec::new());
+    let mut interp = Cowlang::new(Options {
+        program: Program::parse("oom OOM"),
+        input: Box::new(Reader::new(Cursor::new("7\n"))) as Box<dyn Input>,
+        output: &mut output,
+        dialect: Dialect::REFERENCE,
+    });
+
+    interp.run().unwrap();
+    drop(interp);
+    assert_eq!(output.into_inner(), b"7");
+}
+
+#[test]
+fn owned_interpreters_can_move_between_threads() {
+    let mut interp = Cowlang::new(Options {
+        program: Program::parse("oom MoO OOM"),
+        input: Reader::new(Cursor::new(b"41\n".to_vec())),
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::REFERENCE,
+    });
+
+    let output = std::thread::spawn(move || {
+        interp.run().unwrap();
+        interp.into_io().1.into_inner()
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(output, b"42");
+}
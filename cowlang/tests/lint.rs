@@ -0,0 +1,107 @@
+use cowlang::{
+    Program,
+    lint::{self, Lint, LintKind},
+};
+
+fn lints(program: &str) -> Vec<(usize, LintKind)> {
+    lint::lint(&Program::parse(program))
+        .into_iter()
+        .map(|Lint { idx, kind }| (idx, kind))
+        .collect()
+}
+
+#[test]
+fn clean_programs_have_no_lints() {
+    assert_eq!(lints(include_str!("../../samples/hello-world.txt")), []);
+    assert_eq!(
+        lints(include_str!("../../samples/hello-world-loops.txt")),
+        []
+    );
+    assert_eq!(lints("oom MOO MOo moO MoO mOo moo moO OOM"), []);
+}
+
+#[test]
+fn moving_before_the_start() {
+    assert_eq!(lints("mOo"), [(0, LintKind::MoveBeforeStart)]);
+    assert_eq!(lints("moO mOo mOo"), [(2, LintKind::MoveBeforeStart)]);
+    // the cursor may be anywhere after the loop
+    assert_eq!(lints("oom MOO moO MOo moo mOo"), []);
+}
+
+#[test]
+fn endless_loops() {
+    // the value only ever grows
+    assert_eq!(
+        lints("MoO MOO MoO OOM moo OOM"),
+        [(4, LintKind::EndlessLoop), (5, LintKind::Unreachable(1))]
+    );
+    // a forgotten decrement
+    assert_eq!(
+        lints("MoO MoO MoO MOO OOM moo"),
+        [(5, LintKind::EndlessLoop)]
+    );
+    // moving on may reach a zero
+    assert_eq!(lints("MoO MOO moO oom moo"), []);
+}
+
+#[test]
+fn evaluating_values() {
+    assert_eq!(
+        lints("oom mOO"),
+        [
+            (1, LintKind::MoveBeforeStart),
+            (1, LintKind::RecursiveEval),
+            (1, LintKind::InvalidCommand),
+            (1, LintKind::DanglingRegister),
+        ]
+    );
+    assert_eq!(lints("MoO MoO MoO mOO"), [(3, LintKind::RecursiveEval)]);
+    assert_eq!(
+        lints(&format!("{} mOO", "MoO ".repeat(12))),
+        [(12, LintKind::InvalidCommand)]
+    );
+    // 10 is `OOM`
+    assert_eq!(lints(&format!("{} mOO", "MoO ".repeat(10))), []);
+}
+
+#[test]
+fn unreachable_code() {
+    // the value is 0, so the loop is skipped
+    assert_eq!(
+        lints("MOO OOM MoO moo OOM"),
+        [(1, LintKind::Unreachable(3))]
+    );
+    // nothing runs after an error
+    assert_eq!(
+        lints("MoO MoO MoO mOO OOM"),
+        [(3, LintKind::RecursiveEval), (4, LintKind::Unreachable(1))]
+    );
+}
+
+#[test]
+fn dangling_register() {
+    assert_eq!(lints("MoO MMM"), [(1, LintKind::DanglingRegister)]);
+    assert_eq!(lints("MoO MMM moO MMM"), []);
+    // only filled when input is nonzero
+    assert_eq!(
+        lints("oom MOO MMM OOO moo"),
+        [(2, LintKind::DanglingRegister)]
+    );
+}
+
+#[test]
+fn lints_describe_themselves() {
+    let report = lint::lint(&Program::parse("mOo MMM MoO MOO OOM moo OOM"))
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        report,
+        [
+            "0: `mOo` may run on the first cell and do nothing",
+            "5: loop never ends, as the value is never 0 here",
+            "6: 1 command is never run",
+        ]
+    );
+}
@@ -0,0 +1,119 @@
+use cowlang::{
+    Program,
+    optimize::{self, Options, Transformation, TransformationKind},
+};
+
+fn optimized(program: &str) -> (String, Vec<Transformation>) {
+    let optimized = optimize::optimize(&Program::parse(program), &Options::default());
+    (optimized.program.to_string(), optimized.transformations)
+}
+
+#[test]
+fn cancelling_pairs_are_removed() {
+    let options = Options {
+        cancel_increments: true,
+    };
+    let optimized =
+        optimize::optimize(&Program::parse("OOM MoO MOo moO moO mOo mOo OOM"), &options);
+    let (program, transformations) = (optimized.program.to_string(), optimized.transformations);
+
+    assert_eq!(program, "OOM OOM");
+    assert_eq!(
+        transformations,
+        [
+            Transformation {
+                idx: 1,
+                kind: TransformationKind::CancelledIncrement,
+            },
+            Transformation {
+                idx: 3,
+                kind: TransformationKind::CancelledMove,
+            },
+            Transformation {
+                idx: 4,
+                kind: TransformationKind::CancelledMove,
+            },
+        ]
+    );
+}
+
+#[test]
+fn saturating_increments_are_kept_by_default() {
+    let (program, transformations) = optimized("MoO MOo moO mOo");
+
+    assert_eq!(program, "MoO MOo");
+    assert_eq!(
+        transformations,
+        [Transformation {
+            idx: 2,
+            kind: TransformationKind::CancelledMove,
+        }]
+    );
+}
+
+#[test]
+fn clear_loops_are_replaced() {
+    let (program, transformations) = optimized("MoO MoO MOO MOo moo OOM");
+
+    assert_eq!(program, "MoO MoO OOO OOM");
+    assert_eq!(
+        transformations,
+        [Transformation {
+            idx: 2,
+            kind: TransformationKind::ClearLoop,
+        }]
+    );
+}
+
+#[test]
+fn code_after_an_endless_loop_is_dropped() {
+    let (program, transformations) = optimized("oom MoO MOO MoO OOM moo MOo OOM");
+
+    assert_eq!(program, "oom MoO MOO MoO OOM moo");
+    assert_eq!(
+        transformations,
+        [Transformation {
+            idx: 6,
+            kind: TransformationKind::DeadCode(2),
+        }]
+    );
+
+    // a loop that may be skipped is left alone
+    assert_eq!(optimized("MOO MoO OOM moo MOo").0, "MOO MoO OOM moo MOo");
+    assert_eq!(
+        optimized("MoO MOO MOo moO MoO MOO MoO moo moo OOM").0,
+        "MoO MOO MOo moO MoO MOO MoO moo moo OOM"
+    );
+}
+
+#[test]
+fn rewrites_that_would_move_a_jump_are_skipped() {
+    // removing the inner pair would let the outer `moo` match the wrong `MOO`
+    assert_eq!(
+        optimized("MOO MoO moo moO mOo moo").0,
+        "MOO MoO moo moO mOo moo"
+    );
+}
+
+#[test]
+fn padding_goes_once_the_jumps_it_protects_are_gone() {
+    let padded = "MOO moO mOo MOO MOo moO mOo moo moO mOo moo";
+    assert_eq!(optimized(padded).0, "MOO OOO moo");
+}
+
+#[test]
+fn transformations_describe_themselves() {
+    let (_, transformations) = optimized("MOO MOo moo MoO MOO OOM moo MoO");
+    let report = transformations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        report,
+        [
+            "0: replaced `MOO MOo moo` with `OOO`",
+            "7: removed 1 unreachable commands after a loop that never ends",
+        ]
+    );
+}
@@ -0,0 +1,59 @@
+use cowlang::{
+    Command, Program,
+    parse::{self, Span, Spanned},
+};
+use std::io::{BufReader, ErrorKind};
+
+fn spanned(command: Command, offset: usize, line: usize, column: usize) -> Spanned {
+    Spanned {
+        command,
+        span: Span {
+            offset,
+            line,
+            column,
+        },
+    }
+}
+
+#[test]
+fn commands_have_spans() {
+    let text = "MoO moo\n  ünïcode MOO\tOOM\r\nMoOo oom";
+    let expected = [
+        spanned(Command::MoO, 0, 1, 1),
+        spanned(Command::moo, 4, 1, 5),
+        spanned(Command::MOO, 20, 2, 11),
+        spanned(Command::OOM, 24, 2, 15),
+        spanned(Command::oom, 34, 3, 6),
+    ];
+
+    assert_eq!(parse::commands(text).collect::<Vec<_>>(), expected);
+
+    // even when every character is split across reads
+    let read = parse::read(BufReader::with_capacity(1, text.as_bytes()))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(read, expected);
+}
+
+#[test]
+fn invalid_utf8_stops_reading() {
+    let mut stream = parse::read(&b"MoO \xff MoO"[..]);
+
+    assert_eq!(stream.next().unwrap().unwrap().command, Command::MoO);
+    assert_eq!(
+        stream.next().unwrap().unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+    assert!(stream.next().is_none());
+
+    let truncated = parse::read(&"MoO ü".as_bytes()[..5]).collect::<Vec<_>>();
+    assert!(truncated[1].is_err());
+}
+
+#[test]
+fn programs_can_be_read() {
+    let text = include_str!("../../samples/fibonacci.txt");
+    let program = Program::read(BufReader::with_capacity(7, text.as_bytes())).unwrap();
+
+    assert_eq!(&*program, &*Program::parse(text));
+}
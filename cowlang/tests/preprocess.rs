@@ -0,0 +1,193 @@
+mod common;
+
+use common::output;
+use cowlang::{
+    Program,
+    preprocess::{self, Error, ErrorKind, Expansion, Location, MAX_DEPTH, Preprocessed},
+};
+use std::collections::HashMap;
+
+fn files(files: &[(&str, &str)]) -> HashMap<String, String> {
+    files
+        .iter()
+        .map(|&(name, source)| (name.to_string(), source.to_string()))
+        .collect()
+}
+
+fn preprocessed(source: &str) -> Preprocessed {
+    preprocess::preprocess("main.cow", source, &mut HashMap::new()).unwrap()
+}
+
+fn error(source: &str) -> (usize, usize, ErrorKind) {
+    let Error {
+        line, column, kind, ..
+    } = preprocess::preprocess("main.cow", source, &mut HashMap::new()).unwrap_err();
+    (line, column, kind)
+}
+
+#[test]
+fn macros_take_parameters_and_counts() {
+    let source = "
+        #define clear MOO MOo moo
+        #define print(n)
+            MoO*$n Moo @clear
+        #end
+
+        @print(72) @print(105)
+    ";
+
+    assert_eq!(output(preprocessed(source).program, ""), "Hi");
+}
+
+#[test]
+fn groups_repeat() {
+    assert_eq!(
+        preprocessed("(MoO moO)*2 OOM*0 (Moo)").program.to_string(),
+        "MoO moO MoO moO Moo"
+    );
+}
+
+#[test]
+fn plain_cow_is_unchanged() {
+    let source = include_str!("../../samples/hello-world-loops.txt");
+    assert_eq!(
+        preprocessed(source).program.to_string(),
+        Program::parse(source).to_string()
+    );
+}
+
+#[test]
+fn includes_are_expanded_once() {
+    let mut loader = files(&[
+        ("newline.cow", "#define newline MoO*10 Moo OOO"),
+        ("lib.cow", "#include \"newline.cow\"\nOOM"),
+    ]);
+    let source = "#include \"newline.cow\"\n#include \"lib.cow\"\nMoO @newline";
+    let preprocessed = preprocess::preprocess("main.cow", source, &mut loader).unwrap();
+
+    assert_eq!(
+        preprocessed.program.to_string(),
+        format!("OOM MoO {} Moo OOO", ["MoO"; 10].join(" "))
+    );
+    assert_eq!(
+        preprocessed.source_map.files,
+        ["main.cow", "newline.cow", "lib.cow"]
+    );
+}
+
+#[test]
+fn include_cycles_are_errors() {
+    let mut loader = files(&[
+        ("a.cow", "#include \"b.cow\""),
+        ("b.cow", "#include \"a.cow\""),
+    ]);
+    let error = preprocess::preprocess("a.cow", "#include \"b.cow\"", &mut loader).unwrap_err();
+
+    assert_eq!(error.to_string(), "b.cow:1:10: `a.cow` includes itself");
+
+    let error = preprocess::preprocess("a.cow", "#include \"c.cow\"", &mut loader).unwrap_err();
+    assert!(matches!(error.kind, ErrorKind::Include { path, .. } if path == "c.cow"));
+}
+
+#[test]
+fn source_maps_follow_expansions() {
+    let mut loader = files(&[("lib.cow", "\n#define crash(n) $n mOO")]);
+    let source = "#include \"lib.cow\"\nOOM\n  @crash(MoO)";
+    let preprocessed = preprocess::preprocess("main.cow", source, &mut loader).unwrap();
+    let source_map = &preprocessed.source_map;
+    let main = |line, column| Location {
+        file: 0,
+        line,
+        column,
+    };
+
+    assert_eq!(preprocessed.program.to_string(), "OOM MoO mOO");
+
+    // the argument was written at the call
+    let origin = source_map.origin(1).unwrap();
+    assert_eq!(origin.location, main(3, 10));
+
+    let origin = source_map.origin(2).unwrap();
+    assert_eq!(source_map.describe(origin.location), "lib.cow:2:21");
+    assert_eq!(
+        origin.expansions,
+        [Expansion {
+            name: "crash".to_string(),
+            location: main(3, 3),
+        }]
+    );
+    assert_eq!(origin.root(), main(3, 3));
+    assert_eq!(source_map.origin(0).unwrap().root(), main(2, 1));
+}
+
+#[test]
+fn errors_point_at_their_source() {
+    assert_eq!(
+        error("MoO\n @clear"),
+        (2, 2, ErrorKind::UndefinedMacro("clear".to_string()))
+    );
+    assert_eq!(
+        error("#define add(n) MoO*$n\n@add"),
+        (
+            2,
+            1,
+            ErrorKind::WrongArgumentCount {
+                name: "add".to_string(),
+                expected: 1,
+                found: 0,
+            }
+        )
+    );
+    assert_eq!(
+        error("#define a @b\n#define b @a\n@a"),
+        (2, 11, ErrorKind::RecursiveMacro("a".to_string()))
+    );
+    assert_eq!(
+        error("#define a(x) $y\n@a(MoO)"),
+        (1, 14, ErrorKind::UnknownParameter("y".to_string()))
+    );
+    assert_eq!(
+        error("\n#define long\nMoO"),
+        (2, 1, ErrorKind::UnclosedMacro("long".to_string()))
+    );
+    assert_eq!(error("#end"), (1, 1, ErrorKind::UnexpectedEnd));
+    assert_eq!(
+        error("MoO*many"),
+        (1, 5, ErrorKind::InvalidCount("many".to_string()))
+    );
+    assert_eq!(error("(MoO"), (1, 1, ErrorKind::UnclosedParen));
+    assert_eq!(
+        error("#pragma once"),
+        (1, 1, ErrorKind::UnknownDirective("#pragma".to_string()))
+    );
+}
+
+#[test]
+fn repetitions_are_limited() {
+    assert_eq!(error("MoO ()*99999999999"), (1, 5, ErrorKind::TooManyItems));
+    assert_eq!(error("(()*4096)*4096"), (1, 2, ErrorKind::TooManyItems));
+    assert_eq!(
+        error("#define nothing ()*100000\n(@nothing MoO)*1000"),
+        (1, 17, ErrorKind::TooManyItems)
+    );
+    assert!(preprocess::preprocess("main.cow", "(()*4095)*4095", &mut HashMap::new()).is_ok());
+}
+
+#[test]
+fn nesting_is_limited() {
+    let nested = |depth| format!("{}MoO{}", "(".repeat(depth), ")".repeat(depth));
+
+    assert_eq!(
+        error(&nested(10_000)),
+        (1, MAX_DEPTH + 1, ErrorKind::TooDeep)
+    );
+    assert_eq!(preprocessed(&nested(MAX_DEPTH)).program.to_string(), "MoO");
+
+    let chain = (0..MAX_DEPTH)
+        .map(|n| format!("#define m{n} @m{}\n", n + 1))
+        .collect::<String>();
+    assert_eq!(
+        error(&format!("{chain}#define m{MAX_DEPTH} MoO\n@m0")),
+        (MAX_DEPTH, 14, ErrorKind::TooDeep)
+    );
+}
@@ -0,0 +1,443 @@
+use cowlang::{
+    Command, Cowlang, Dialect, Error, Input, InputKind, Options, Program, Reader, Status, Suspend,
+    Value, Writer,
+};
+use proptest::prelude::*;
+use std::io::{self, Cursor};
+
+const MAX_STEPS: usize = 500;
+
+fn straight_command() -> impl Strategy<Value = Command> {
+    (0u32..12)
+        .prop_map(|value| Command::try_from(value).unwrap())
+        .prop_filter("loops are generated separately", |command| {
+            !matches!(command, Command::MOO | Command::moo)
+        })
+}
+
+fn balanced_program() -> impl Strategy<Value = Vec<Command>> {
+    let leaf = prop::collection::vec(straight_command(), 0..8);
+
+    leaf.prop_recursive(4, 64, 4, |inner| {
+        prop::collection::vec(
+            prop_oneof![
+                inner.clone(),
+                inner.prop_map(|body| {
+                    let mut commands = vec![Command::MOO];
+                    commands.extend(body);
+                    commands.push(Command::moo);
+                    commands
+                }),
+            ],
+            1..4,
+        )
+        .prop_map(|parts| parts.concat())
+    })
+}
+
+fn input_value(kind: InputKind, raw: u32) -> Value {
+    match kind {
+        InputKind::Int => Value::Int(raw),
+        InputKind::Char => Value::Char(char::from_u32('a' as u32 + raw % 26).unwrap()),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Run {
+    memory: Vec<u32>,
+    memory_idx: usize,
+    register: Option<u32>,
+    output: Vec<u8>,
+    error: Option<String>,
+}
+
+fn describe(error: Error) -> String {
+    match error {
+        Error::Io(error) if error.kind() == io::ErrorKind::UnexpectedEof => "eof".to_string(),
+        error => error.to_string(),
+    }
+}
+
+fn finish<I: Input>(interp: Cowlang<'_, I, Writer<Vec<u8>>>, error: Option<String>) -> Run {
+    let memory = interp.memory().to_vec();
+    let memory_idx = interp.memory_idx();
+    let register = interp.register();
+    let (_, output) = interp.into_io();
+
+    Run {
+        memory,
+        memory_idx,
+        register,
+        output: output.into_inner(),
+        error,
+    }
+}
+
+/// Advances until the program ends or `limit` steps have run, answering each
+/// request for input from `raw` and recording the text a blocking reader would
+/// need to produce the same values. Returns how the program ended, if it did.
+fn drive(
+    interp: &mut Cowlang<'_, Suspend, Writer<Vec<u8>>>,
+    raw: &mut std::slice::Iter<u32>,
+    text: &mut String,
+    steps: &mut usize,
+    limit: usize,
+) -> Option<Option<String>> {
+    loop {
+        if *steps == limit {
+            return None;
+        }
+
+        match interp.advance() {
+            Ok(Status::Completed) => return Some(None),
+            Ok(Status::Running) => *steps += 1,
+            Ok(Status::NeedsInput(kind)) => {
+                let Some(&raw) = raw.next() else {
+                    return Some(Some("eof".to_string()));
+                };
+
+                let value = input_value(kind, raw);
+                match value {
+                    Value::Int(int) => text.push_str(&format!("{int}\n")),
+                    Value::Char(char) => text.push(char),
+                }
+                interp.provide_input(value).unwrap();
+            }
+            Err(error) => return Some(Some(describe(error))),
+        }
+    }
+}
+
+fn suspended(commands: &[Command]) -> Cowlang<'_, Suspend, Writer<Vec<u8>>> {
+    Cowlang::new(Options {
+        program: Program::new(commands),
+        input: Suspend,
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::REFERENCE,
+    })
+}
+
+/// Runs with suspending input, answering each request from `raw` and
+/// recording the text a blocking reader would need to produce the same values.
+fn run_suspended(commands: &[Command], raw: &[u32]) -> (Run, String) {
+    let mut interp = suspended(commands);
+    let mut text = String::new();
+    let error = drive(&mut interp, &mut raw.iter(), &mut text, &mut 0, MAX_STEPS).flatten();
+
+    (finish(interp, error), text)
+}
+
+/// Like [`run_suspended`], but restores the interpreter from a snapshot taken
+/// after `at` steps and finishes the run with that.
+fn run_restored(commands: &[Command], raw: &[u32], at: usize) -> Run {
+    let mut interp = suspended(commands);
+    let mut raw = raw.iter();
+    let mut steps = 0;
+
+    if let Some(error) = drive(&mut interp, &mut raw, &mut String::new(), &mut steps, at) {
+        return finish(interp, error);
+    }
+
+    let output = Writer::new(interp.output().get_ref().clone());
+    let mut restored = Cowlang::restore(interp.snapshot(), Suspend, output).unwrap();
+    let error = drive(
+        &mut restored,
+        &mut raw,
+        &mut String::new(),
+        &mut steps,
+        MAX_STEPS,
+    )
+    .flatten();
+
+    finish(restored, error)
+}
+
+fn run_blocking(commands: &[Command], text: String) -> Run {
+    let mut interp = Cowlang::new(Options {
+        program: Program::new(commands),
+        input: Reader::new(Cursor::new(text)),
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::REFERENCE,
+    });
+
+    let mut steps = 0;
+
+    let error = loop {
+        if steps == MAX_STEPS {
+            break None;
+        }
+
+        match interp.advance() {
+            Ok(Status::Completed) => break None,
+            Ok(_) => steps += 1,
+            Err(error) => break Some(describe(error)),
+        }
+    };
+
+    finish(interp, error)
+}
+
+/// Runs with a fixed input, returning the output and error if the program
+/// finishes within `MAX_STEPS`.
+fn run_to_end(commands: &[Command]) -> Option<(Vec<u8>, Option<String>)> {
+    let mut interp = Cowlang::new(Options {
+        program: Program::new(commands),
+        input: Reader::new(Cursor::new("5\n7\nxyz\n3\n")),
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::REFERENCE,
+    });
+
+    let mut steps = 0;
+
+    let error = loop {
+        if steps == MAX_STEPS {
+            return None;
+        }
+
+        match interp.advance() {
+            Ok(Status::Completed) => break None,
+            Ok(_) => steps += 1,
+            Err(error) => break Some(describe(error)),
+        }
+    };
+
+    Some((interp.into_io().1.into_inner(), error))
+}
+
+proptest! {
+    #[test]
+    fn program_text_round_trips(commands in prop::collection::vec(0u32..12, 0..64)) {
+        let commands = commands
+            .into_iter()
+            .map(|value| Command::try_from(value).unwrap())
+            .collect::<Vec<_>>();
+        let program = Program::new(&commands[..]);
+
+        prop_assert_eq!(&*Program::parse(&program.to_string()), &commands[..]);
+    }
+
+    #[test]
+    fn streaming_parses_like_splitting_words(
+        words in prop::collection::vec("(MoO|moo|OOM|mOO|MoOo|ünï|\\PC{0,4})", 0..24),
+        spaces in prop::collection::vec("[ \t\n\r\u{a0}\u{2003}\u{3000}]{1,3}", 24),
+        capacity in 1usize..8,
+    ) {
+        let text = words
+            .iter()
+            .zip(&spaces)
+            .map(|(word, space)| format!("{word}{space}"))
+            .collect::<String>();
+        let expected = text
+            .split_whitespace()
+            .filter_map(|word| word.parse::<Command>().ok())
+            .collect::<Vec<_>>();
+
+        let parsed = cowlang::parse::commands(&text).collect::<Vec<_>>();
+        let read = cowlang::parse::read(io::BufReader::with_capacity(capacity, text.as_bytes()))
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+
+        prop_assert_eq!(parsed.iter().map(|spanned| spanned.command).collect::<Vec<_>>(), expected);
+        prop_assert_eq!(&read, &parsed);
+        for spanned in parsed {
+            prop_assert!(text[spanned.span.offset..].starts_with(&spanned.command.to_string()));
+        }
+    }
+
+    #[test]
+    fn binary_encoding_round_trips(commands in prop::collection::vec(0u32..12, 0..64)) {
+        let commands = commands
+            .into_iter()
+            .map(|value| Command::try_from(value).unwrap())
+            .collect::<Vec<_>>();
+        let encoded = cowlang::binary::encode(&commands);
+
+        prop_assert_eq!(encoded.len(), 13 + commands.len().div_ceil(2));
+        prop_assert_eq!(&*cowlang::binary::decode(&encoded).unwrap(), &commands[..]);
+    }
+
+    #[test]
+    fn parsing_ignores_comments(
+        commands in balanced_program(),
+        comments in prop::collection::vec("[a-zA-Z0-9#!]{1,6}", 0..16),
+    ) {
+        let comments = comments
+            .into_iter()
+            .filter(|word| word.parse::<Command>().is_err())
+            .collect::<Vec<_>>();
+
+        let mut text = String::new();
+        for (i, command) in commands.iter().enumerate() {
+            if let Some(comment) = comments.get(i % (comments.len() + 1)) {
+                text.push_str(comment);
+                text.push('\n');
+            }
+            text.push_str(&format!("{command} "));
+        }
+
+        prop_assert_eq!(&*Program::parse(&text), &commands[..]);
+    }
+
+    #[test]
+    fn formatting_keeps_the_program_and_is_idempotent(
+        commands in balanced_program(),
+        words_per_line in 1usize..12,
+        indent in 0usize..6,
+    ) {
+        let options = cowlang::format::Options { words_per_line, indent };
+        let formatted = cowlang::format::program(&commands, &options);
+
+        prop_assert_eq!(&*Program::parse(&formatted), &commands[..]);
+        prop_assert_eq!(cowlang::format::source(&formatted, &options), formatted);
+    }
+
+    #[test]
+    fn suspended_and_blocking_input_agree(
+        commands in balanced_program(),
+        raw in prop::collection::vec(0u32..16, 0..32),
+    ) {
+        let (suspended, text) = run_suspended(&commands, &raw);
+        let blocking = run_blocking(&commands, text);
+
+        prop_assert_eq!(suspended, blocking);
+    }
+
+    #[test]
+    fn snapshots_round_trip(
+        commands in balanced_program(),
+        raw in prop::collection::vec(0u32..16, 0..32),
+        at in 0..MAX_STEPS,
+    ) {
+        let (uninterrupted, _) = run_suspended(&commands, &raw);
+
+        prop_assert_eq!(run_restored(&commands, &raw, at), uninterrupted);
+    }
+
+    #[test]
+    fn optimizing_keeps_the_behaviour(
+        commands in prop_oneof![
+            balanced_program(),
+            prop::collection::vec(
+                (0u32..12).prop_map(|value| Command::try_from(value).unwrap()),
+                0..32,
+            ),
+        ],
+    ) {
+        let optimized = cowlang::optimize::optimize(&commands, &Default::default());
+        prop_assert!(optimized.program.len() <= commands.len());
+
+        if let Some(expected) = run_to_end(&commands) {
+            prop_assert_eq!(run_to_end(&optimized.program), Some(expected));
+        }
+    }
+
+    #[test]
+    fn commands_that_run_are_not_unreachable(
+        commands in prop_oneof![
+            balanced_program(),
+            prop::collection::vec(
+                (0u32..12).prop_map(|value| Command::try_from(value).unwrap()),
+                0..32,
+            ),
+        ],
+    ) {
+        let mut interp = Cowlang::new(Options {
+            program: Program::new(&commands[..]),
+            input: Reader::new(Cursor::new("5\n7\nxyz\n3\n")),
+            output: Writer::new(Vec::new()),
+            dialect: Dialect::REFERENCE,
+        });
+        let mut run = vec![false; commands.len()];
+
+        for _ in 0..MAX_STEPS {
+            if interp.completed() {
+                break;
+            }
+            run[interp.program_idx()] = true;
+            if interp.advance().is_err() {
+                break;
+            }
+        }
+
+        for lint in cowlang::lint::lint(&commands) {
+            if let cowlang::lint::LintKind::Unreachable(count) = lint.kind {
+                prop_assert!(!run[lint.idx..lint.idx + count].contains(&true), "{}", lint);
+            }
+        }
+    }
+
+    #[test]
+    fn analysis_covers_every_run(
+        commands in prop_oneof![
+            balanced_program(),
+            prop::collection::vec(
+                (0u32..12).prop_map(|value| Command::try_from(value).unwrap()),
+                0..32,
+            ),
+        ],
+    ) {
+        let analysis = Program::new(&commands[..]).analyze();
+        let mut interp = Cowlang::new(Options {
+            program: Program::new(&commands[..]),
+            input: Reader::new(Cursor::new("5\n7\nxyz\n3\n")),
+            output: Writer::new(Vec::new()),
+            dialect: Dialect::REFERENCE,
+        });
+
+        for _ in 0..MAX_STEPS {
+            let point = if interp.completed() {
+                analysis.exit()
+            } else {
+                analysis.point(interp.program_idx())
+            };
+            let point = point.expect("a point that runs is reachable");
+
+            prop_assert!(point.min_offset() <= interp.memory_idx());
+            prop_assert!(point.offset().is_none_or(|offset| offset == interp.memory_idx()));
+            prop_assert!(point.current().may_be(interp.current_value()));
+            for (offset, &value) in interp.memory().iter().enumerate() {
+                prop_assert!(point.cell(offset).may_be(value), "cell {}", offset);
+            }
+
+            if interp.completed() || interp.advance().is_err() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn loop_shifts_match_each_pass(commands in balanced_program()) {
+        let analysis = Program::new(&commands[..]).analyze();
+        let mut interp = Cowlang::new(Options {
+            program: Program::new(&commands[..]),
+            input: Reader::new(Cursor::new("5\n7\nxyz\n3\n")),
+            output: Writer::new(Vec::new()),
+            dialect: Dialect::REFERENCE,
+        });
+        // where the cursor was when each loop's body was last entered
+        let mut entered = vec![None; commands.len()];
+
+        for _ in 0..MAX_STEPS {
+            if interp.completed() {
+                break;
+            }
+
+            let idx = interp.program_idx();
+            for lp in analysis.loops() {
+                if lp.start == idx {
+                    entered[idx] = Some(interp.memory_idx());
+                }
+                if lp.end == idx
+                    && let (Some(shift), Some(start)) = (lp.shift, entered[lp.start])
+                {
+                    prop_assert_eq!(interp.memory_idx() as isize - start as isize, shift);
+                    entered[lp.start] = Some(interp.memory_idx());
+                }
+            }
+
+            if interp.advance().is_err() {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use std::path::Path;
+
+#[test]
+fn samples_match_expected_output() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../samples");
+    let outcomes = cowlang::golden::run(&dir, &Default::default()).unwrap();
+
+    assert!(!outcomes.is_empty());
+
+    for outcome in outcomes {
+        assert!(outcome.passed(), "{}: {:?}", outcome.name, outcome.verdict);
+    }
+}
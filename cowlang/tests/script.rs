@@ -0,0 +1,162 @@
+mod common;
+
+use cowlang::{
+    batch::{self, Case, Job, Verdict},
+    script::{self, Error, ErrorKind},
+};
+use std::{fs, path::Path};
+
+fn output(source: &str, input: &str) -> String {
+    common::output(script::compile(source).unwrap(), input)
+}
+
+fn error(source: &str) -> (usize, usize, ErrorKind) {
+    let Error { line, column, kind } = script::compile(source).unwrap_err();
+    (line, column, kind)
+}
+
+#[test]
+fn examples_match_their_expected_output() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples");
+    let mut jobs = Vec::new();
+
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.extension().is_none_or(|ext| ext != "cows") {
+            continue;
+        }
+
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let source = fs::read_to_string(&path).unwrap();
+
+        jobs.push(Job {
+            program: script::compile(&source).unwrap_or_else(|error| panic!("{name}: {error}")),
+            case: Case {
+                name: name.clone(),
+                input: fs::read_to_string(path.with_extension("in")).unwrap_or_default(),
+                expected: fs::read_to_string(path.with_extension("out")).unwrap(),
+                max_steps: None,
+            },
+            name,
+        });
+    }
+
+    assert!(!jobs.is_empty());
+
+    for outcome in batch::run(&jobs, &batch::Options::default()) {
+        if let Verdict::Fail { diff } = &outcome.verdict {
+            panic!("{}:\n{diff}", outcome.name);
+        }
+        assert!(outcome.passed(), "{}: {:?}", outcome.name, outcome.verdict);
+    }
+}
+
+#[test]
+fn operators_match_saturating_arithmetic() {
+    let values = [0u32, 1, 2, 3, 7, 12];
+
+    for a in values {
+        for b in values {
+            let source = format!(
+                "let a = {a}; let b = {b};
+                print a + b; print \" \"; print a - b; print \" \";
+                print a * b; print \" \"; print a / b; print \" \";
+                print a % b; print \" \"; print a == b; print a != b;
+                print a < b; print a <= b; print a > b; print a >= b;
+                print a && b; print a || b; print !a;"
+            );
+
+            let expected = format!(
+                "{} {} {} {} {} {}{}{}{}{}{}{}{}{}",
+                a + b,
+                a.saturating_sub(b),
+                a * b,
+                a.checked_div(b).unwrap_or(0),
+                a.checked_rem(b).unwrap_or(a),
+                (a == b) as u32,
+                (a != b) as u32,
+                (a < b) as u32,
+                (a <= b) as u32,
+                (a > b) as u32,
+                (a >= b) as u32,
+                (a != 0 && b != 0) as u32,
+                (a != 0 || b != 0) as u32,
+                (a == 0) as u32,
+            );
+
+            assert_eq!(output(&source, ""), expected, "a = {a}, b = {b}");
+        }
+    }
+}
+
+#[test]
+fn precedence_follows_arithmetic() {
+    assert_eq!(output("print 2 + 3 * 4 - (5 - 1) / 2;", ""), "12");
+    assert_eq!(output("print 1 + 1 == 2 && !(3 < 2);", ""), "1");
+    assert_eq!(output("print 300 * 300;", ""), "90000");
+}
+
+#[test]
+fn nested_conditionals_and_loops() {
+    let source = "
+        let i = 0;
+        let total = 0;
+        while i < 4 {
+            let j = 0;
+            while j < i {
+                if (i + j) % 2 == 0 { total = total + 1; } else { total = total + 10; }
+                j = j + 1;
+            }
+            i = i + 1;
+        }
+        print total;
+    ";
+
+    assert_eq!(output(source, ""), "42");
+}
+
+#[test]
+fn reads_integers() {
+    assert_eq!(output("let x = 0; read x; print x * 2;", "21\n"), "42");
+}
+
+#[test]
+fn errors_point_at_the_source() {
+    assert_eq!(
+        error("let x = 1;\nprint y;"),
+        (2, 7, ErrorKind::UndefinedVariable("y".to_string()))
+    );
+    assert_eq!(
+        error("x = 1; let x = 2;"),
+        (1, 1, ErrorKind::UndefinedVariable("x".to_string()))
+    );
+    assert_eq!(
+        error("let x = 1;\nlet x = 2;"),
+        (2, 5, ErrorKind::DuplicateVariable("x".to_string()))
+    );
+    assert_eq!(
+        error("let x = 1"),
+        (
+            1,
+            10,
+            ErrorKind::Expected {
+                expected: "`;`".to_string(),
+                found: "end of input".to_string()
+            }
+        )
+    );
+    assert_eq!(error("print \"oops"), (1, 7, ErrorKind::UnterminatedString));
+    assert_eq!(
+        error("print 1 $ 2;"),
+        (1, 9, ErrorKind::UnexpectedChar('$'))
+    );
+    assert_eq!(
+        error("print 99999999999;"),
+        (1, 7, ErrorKind::IntegerTooLarge("99999999999".to_string()))
+    );
+    assert_eq!(
+        script::compile("let while = 1;").unwrap_err().to_string(),
+        "1:5: expected a variable name, found `while`"
+    );
+}
@@ -0,0 +1,84 @@
+#![allow(non_snake_case)]
+
+use cowlang::{
+    Command, Cowlang, Dialect, Error, InputKind, Options, Program, Snapshot, Status, Suspend,
+    Value, Writer,
+};
+use serde_json::json;
+
+#[test]
+fn commands_are_serialized_by_name() {
+    assert_eq!(serde_json::to_value(Command::mOO).unwrap(), json!("mOO"));
+    assert_eq!(
+        serde_json::from_value::<Command>(json!("OOM")).unwrap(),
+        Command::OOM
+    );
+    assert!(serde_json::from_value::<Command>(json!("MOOO")).is_err());
+    assert!(serde_json::from_value::<Command>(json!(10)).is_err());
+
+    let program = Program::parse("MoO MOO OOM MOo moo");
+    let value = serde_json::to_value(&program).unwrap();
+
+    assert_eq!(value, json!(["MoO", "MOO", "OOM", "MOo", "moo"]));
+    assert_eq!(serde_json::from_value::<Program>(value).unwrap(), program);
+}
+
+#[test]
+fn snapshots_resume_elsewhere() {
+    let mut interp = Cowlang::new(Options {
+        program: Program::parse("MoO MoO mOO oom OOM mOo OOM"),
+        input: Suspend,
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::STRUCTURED,
+    });
+    assert_eq!(interp.run().unwrap(), Status::NeedsInput(InputKind::Int));
+    interp.provide_input(Value::Int(41)).unwrap();
+
+    let json = serde_json::to_string(&interp.snapshot()).unwrap();
+    let snapshot = serde_json::from_str::<Snapshot>(&json).unwrap();
+    assert_eq!(snapshot, interp.snapshot());
+    assert_eq!(snapshot.pending_input, Some(Value::Int(41)));
+
+    let mut resumed = Cowlang::restore(snapshot, Suspend, Writer::new(Vec::new())).unwrap();
+    assert_eq!(resumed.run().unwrap(), Status::Completed);
+    assert_eq!(resumed.output().get_ref(), b"412");
+    assert_eq!(resumed.dispatches().count(2, Command::moO), 1);
+    assert_eq!(resumed.dialect(), Dialect::STRUCTURED);
+}
+
+#[test]
+fn snapshots_out_of_bounds_are_rejected() {
+    let interp = Cowlang::new(Options {
+        program: Program::parse("moO"),
+        input: Suspend,
+        output: Writer::new(Vec::new()),
+        dialect: Dialect::REFERENCE,
+    });
+    let mut value = serde_json::to_value(interp.snapshot()).unwrap();
+    value["memory_idx"] = json!(1);
+
+    let snapshot = serde_json::from_value::<Snapshot>(value).unwrap();
+    assert!(matches!(
+        Cowlang::restore(snapshot, Suspend, Writer::new(Vec::new())),
+        Err(Error::InvalidSnapshot)
+    ));
+}
+
+#[test]
+fn errors_round_trip() {
+    let errors = [
+        (Error::RecursiveEval, json!("RecursiveEval")),
+        (
+            Error::Io(std::io::Error::other("disk on fire")),
+            json!({ "Io": "disk on fire" }),
+        ),
+    ];
+
+    for (error, expected) in errors {
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value, expected);
+
+        let decoded = serde_json::from_value::<Error>(value).unwrap();
+        assert_eq!(decoded.to_string(), error.to_string());
+    }
+}
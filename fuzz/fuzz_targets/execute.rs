@@ -0,0 +1,36 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use cowlang::{Command, Cowlang, Dialect, Options, Program, Reader, Status, Writer};
+use libfuzzer_sys::fuzz_target;
+use std::io::{Cursor, sink};
+
+const MAX_STEPS: usize = 10_000;
+
+#[derive(Debug, Arbitrary)]
+struct Case<'a> {
+    program: Vec<u8>,
+    input: &'a [u8],
+}
+
+fuzz_target!(|case: Case| {
+    let commands = case
+        .program
+        .iter()
+        .map(|&byte| Command::try_from(u32::from(byte) % 12).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut interp = Cowlang::new(Options {
+        program: Program::new(commands),
+        input: Reader::new(Cursor::new(case.input)),
+        output: Writer::new(sink()),
+        dialect: Dialect::REFERENCE,
+    });
+
+    for _ in 0..MAX_STEPS {
+        match interp.advance() {
+            Ok(Status::Running) => {}
+            Ok(_) | Err(_) => break,
+        }
+    }
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use cowlang::Program;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    let program = Program::parse(text);
+    assert_eq!(*Program::parse(&program.to_string()), *program);
+});